@@ -0,0 +1,526 @@
+// The bank and CSV reader as they were before the fixed-point Amount, trimmed
+// to the path a run of the CLI takes. The BigDecimal amounts they used are a
+// type parameter, so the same code runs with Amount in their place.
+#![allow(dead_code)]
+
+use std::{collections::HashMap, io, str::FromStr};
+
+use bigdecimal::BigDecimal;
+use csv::{StringRecord, Trim};
+use serde::Deserialize;
+use simple_xact::amount::Amount;
+
+// The generated input never overflows, so Amount can panic where the
+// BigDecimal code had nothing to check.
+pub trait Number: Clone + Default + PartialOrd + FromStr {
+    fn add(&mut self, amount: &Self);
+    fn sub(&mut self, amount: &Self);
+    fn neg(&self) -> Self;
+}
+
+impl Number for BigDecimal {
+    fn add(&mut self, amount: &Self) {
+        *self += amount;
+    }
+
+    fn sub(&mut self, amount: &Self) {
+        *self -= amount;
+    }
+
+    fn neg(&self) -> Self {
+        -self
+    }
+}
+
+impl Number for Amount {
+    fn add(&mut self, amount: &Self) {
+        *self = self.checked_add(*amount).expect("amount overflow");
+    }
+
+    fn sub(&mut self, amount: &Self) {
+        *self = self.checked_sub(*amount).expect("amount overflow");
+    }
+
+    fn neg(&self) -> Self {
+        self.checked_neg().expect("amount overflow")
+    }
+}
+
+pub struct Bank<N> {
+    accounts: HashMap<u16, Account<N>>,
+    transactions: HashMap<u32, Transaction<N>>,
+}
+
+impl<N> Default for Bank<N> {
+    fn default() -> Self {
+        Self {
+            accounts: HashMap::new(),
+            transactions: HashMap::new(),
+        }
+    }
+}
+
+impl<N: Number> Bank<N> {
+    pub fn apply(&mut self, transaction: Transaction<N>) -> Result<AppliedEffect<N>, Rejection> {
+        self.check_not_frozen(&transaction)?;
+        let effect = match transaction {
+            Transaction::Deposit {
+                client, tx, amount, ..
+            } => self.apply_deposit(client, tx, amount),
+            Transaction::Withdrawal {
+                client, tx, amount, ..
+            } => self.appy_withdrawal(client, tx, amount),
+            Transaction::Dispute {
+                client,
+                referenced_tx,
+            } => self.apply_dispute(client, referenced_tx),
+            Transaction::Resolve {
+                client,
+                referenced_tx,
+            } => self.apply_resolve(client, referenced_tx),
+            Transaction::ChargeBack {
+                client,
+                referenced_tx,
+            } => self.apply_chargeback(client, referenced_tx),
+        }?;
+        self.commit(&effect);
+        Ok(effect)
+    }
+
+    pub fn balances(&self) -> impl Iterator<Item = &Account<N>> {
+        self.accounts.values()
+    }
+
+    fn apply_deposit(
+        &self,
+        client: u16,
+        tx: u32,
+        amount: N,
+    ) -> Result<AppliedEffect<N>, Rejection> {
+        self.check_unique(client, tx)?;
+        Ok(AppliedEffect::Credited { client, tx, amount })
+    }
+
+    fn appy_withdrawal(
+        &self,
+        client: u16,
+        tx: u32,
+        amount: N,
+    ) -> Result<AppliedEffect<N>, Rejection> {
+        self.check_unique(client, tx)?;
+        match self.existing_acct(client)?.available >= amount {
+            true => Ok(AppliedEffect::Debited { client, tx, amount }),
+            false => Err(Rejection::InsufficientFunds { client, tx }),
+        }
+    }
+
+    fn apply_dispute(
+        &self,
+        client: u16,
+        referenced_tx: u32,
+    ) -> Result<AppliedEffect<N>, Rejection> {
+        self.existing_acct(client)?;
+        let amount = match self.owned_transaction(client, referenced_tx)? {
+            Transaction::Deposit {
+                disputed: false,
+                amount,
+                ..
+            } => amount.clone(),
+            Transaction::Withdrawal {
+                disputed: false,
+                amount,
+                ..
+            } => amount.neg(),
+            _ => {
+                return Err(Rejection::AlreadyDisputed {
+                    client,
+                    referenced_tx,
+                })
+            }
+        };
+        Ok(AppliedEffect::Held {
+            client,
+            tx: referenced_tx,
+            amount,
+        })
+    }
+
+    fn apply_resolve(
+        &self,
+        client: u16,
+        referenced_tx: u32,
+    ) -> Result<AppliedEffect<N>, Rejection> {
+        Ok(AppliedEffect::Released {
+            client,
+            tx: referenced_tx,
+            amount: self.disputed_amount(client, referenced_tx)?,
+        })
+    }
+
+    fn apply_chargeback(
+        &self,
+        client: u16,
+        referenced_tx: u32,
+    ) -> Result<AppliedEffect<N>, Rejection> {
+        Ok(AppliedEffect::Reversed {
+            client,
+            tx: referenced_tx,
+            amount: self.disputed_amount(client, referenced_tx)?,
+        })
+    }
+
+    fn commit(&mut self, effect: &AppliedEffect<N>) {
+        let acct = self
+            .accounts
+            .entry(effect.client())
+            .or_insert_with(|| new_acct(effect.client()));
+        match effect {
+            AppliedEffect::Credited { tx, amount, .. } => {
+                acct_credit(acct, &mut self.transactions, *tx, amount)
+            }
+            AppliedEffect::Debited { tx, amount, .. } => {
+                acct_debit(acct, &mut self.transactions, *tx, amount)
+            }
+            AppliedEffect::Held { tx, amount, .. } => {
+                acct_hold(acct, &mut self.transactions, *tx, amount)
+            }
+            AppliedEffect::Released { tx, amount, .. } => {
+                acct_release(acct, &mut self.transactions, *tx, amount)
+            }
+            AppliedEffect::Reversed { tx, amount, .. } => {
+                acct_reverse(acct, &mut self.transactions, *tx, amount)
+            }
+        }
+    }
+
+    fn check_not_frozen(&self, transaction: &Transaction<N>) -> Result<(), Rejection> {
+        let client = transaction.client();
+        match self.accounts.get(&client) {
+            Some(acct) if acct.locked => Err(Rejection::AccountLocked { client }),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_unique(&self, client: u16, tx: u32) -> Result<(), Rejection> {
+        match self.transactions.contains_key(&tx) {
+            true => Err(Rejection::DuplicateTransaction { client, tx }),
+            false => Ok(()),
+        }
+    }
+
+    fn existing_acct(&self, client: u16) -> Result<&Account<N>, Rejection> {
+        self.accounts
+            .get(&client)
+            .ok_or(Rejection::UnknownClient { client })
+    }
+
+    fn owned_transaction(
+        &self,
+        client: u16,
+        referenced_tx: u32,
+    ) -> Result<&Transaction<N>, Rejection> {
+        self.transactions
+            .get(&referenced_tx)
+            .filter(|transaction| transaction.client() == client)
+            .ok_or(Rejection::UnknownTransaction {
+                client,
+                referenced_tx,
+            })
+    }
+
+    fn disputed_amount(&self, client: u16, referenced_tx: u32) -> Result<N, Rejection> {
+        self.existing_acct(client)?;
+        match self.owned_transaction(client, referenced_tx)? {
+            Transaction::Deposit {
+                disputed: true,
+                amount,
+                ..
+            } => Ok(amount.clone()),
+            Transaction::Withdrawal {
+                disputed: true,
+                amount,
+                ..
+            } => Ok(amount.neg()),
+            _ => Err(Rejection::NotDisputed {
+                client,
+                referenced_tx,
+            }),
+        }
+    }
+}
+
+fn new_acct<N: Number>(client: u16) -> Account<N> {
+    Account {
+        client,
+        available: N::default(),
+        held: N::default(),
+        locked: false,
+    }
+}
+
+fn acct_credit<N: Number>(
+    acct: &mut Account<N>,
+    transactions: &mut HashMap<u32, Transaction<N>>,
+    tx: u32,
+    amount: &N,
+) {
+    acct.available.add(amount);
+    transactions.insert(
+        tx,
+        Transaction::Deposit {
+            client: acct.client,
+            tx,
+            amount: amount.clone(),
+            disputed: false,
+        },
+    );
+}
+
+fn acct_debit<N: Number>(
+    acct: &mut Account<N>,
+    transactions: &mut HashMap<u32, Transaction<N>>,
+    tx: u32,
+    amount: &N,
+) {
+    acct.available.sub(amount);
+    transactions.insert(
+        tx,
+        Transaction::Withdrawal {
+            client: acct.client,
+            tx,
+            amount: amount.clone(),
+            disputed: false,
+        },
+    );
+}
+
+fn acct_hold<N: Number>(
+    acct: &mut Account<N>,
+    transactions: &mut HashMap<u32, Transaction<N>>,
+    referenced_tx: u32,
+    amount: &N,
+) {
+    acct.available.sub(amount);
+    acct.held.add(amount);
+    set_disputed(transactions, referenced_tx, true);
+}
+
+fn acct_release<N: Number>(
+    acct: &mut Account<N>,
+    transactions: &mut HashMap<u32, Transaction<N>>,
+    referenced_tx: u32,
+    amount: &N,
+) {
+    acct.available.add(amount);
+    acct.held.sub(amount);
+    set_disputed(transactions, referenced_tx, false);
+}
+
+fn acct_reverse<N: Number>(
+    acct: &mut Account<N>,
+    transactions: &mut HashMap<u32, Transaction<N>>,
+    referenced_tx: u32,
+    amount: &N,
+) {
+    acct.held.sub(amount);
+    acct.locked = true;
+    set_disputed(transactions, referenced_tx, false);
+}
+
+fn set_disputed<N>(
+    transactions: &mut HashMap<u32, Transaction<N>>,
+    referenced_tx: u32,
+    value: bool,
+) {
+    if let Some(Transaction::Deposit { disputed, .. } | Transaction::Withdrawal { disputed, .. }) =
+        transactions.get_mut(&referenced_tx)
+    {
+        *disputed = value;
+    }
+}
+
+pub enum Transaction<N> {
+    Deposit {
+        client: u16,
+        tx: u32,
+        amount: N,
+        disputed: bool,
+    },
+    Withdrawal {
+        client: u16,
+        tx: u32,
+        amount: N,
+        disputed: bool,
+    },
+    Dispute {
+        client: u16,
+        referenced_tx: u32,
+    },
+    Resolve {
+        client: u16,
+        referenced_tx: u32,
+    },
+    ChargeBack {
+        client: u16,
+        referenced_tx: u32,
+    },
+}
+
+impl<N> Transaction<N> {
+    fn client(&self) -> u16 {
+        match *self {
+            Transaction::Deposit { client, .. }
+            | Transaction::Withdrawal { client, .. }
+            | Transaction::Dispute { client, .. }
+            | Transaction::Resolve { client, .. }
+            | Transaction::ChargeBack { client, .. } => client,
+        }
+    }
+}
+
+pub struct Account<N> {
+    pub client: u16,
+    pub available: N,
+    pub held: N,
+    pub locked: bool,
+}
+
+pub enum AppliedEffect<N> {
+    Credited { client: u16, tx: u32, amount: N },
+    Debited { client: u16, tx: u32, amount: N },
+    Held { client: u16, tx: u32, amount: N },
+    Released { client: u16, tx: u32, amount: N },
+    Reversed { client: u16, tx: u32, amount: N },
+}
+
+impl<N> AppliedEffect<N> {
+    fn client(&self) -> u16 {
+        match *self {
+            AppliedEffect::Credited { client, .. }
+            | AppliedEffect::Debited { client, .. }
+            | AppliedEffect::Held { client, .. }
+            | AppliedEffect::Released { client, .. }
+            | AppliedEffect::Reversed { client, .. } => client,
+        }
+    }
+}
+
+pub enum Rejection {
+    InsufficientFunds { client: u16, tx: u32 },
+    UnknownClient { client: u16 },
+    UnknownTransaction { client: u16, referenced_tx: u32 },
+    AlreadyDisputed { client: u16, referenced_tx: u32 },
+    NotDisputed { client: u16, referenced_tx: u32 },
+    AccountLocked { client: u16 },
+    DuplicateTransaction { client: u16, tx: u32 },
+}
+
+// Unparsed rows are skipped, so they carry no raw record or reason here.
+pub struct ParseError;
+
+pub fn processs_transactions_from_csv<N: Number, ProcessCallback>(
+    reader: impl io::Read,
+    mut process: ProcessCallback,
+) where
+    ProcessCallback: FnMut(Result<Transaction<N>, ParseError>),
+{
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(Trim::All)
+        .from_reader(reader);
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(_) => return process(Err(ParseError)),
+    };
+    for record in reader.records() {
+        match record {
+            Ok(record) => {
+                let raw = RawRecord::from(&record);
+                let transaction = try_transaction_from(&headers, &record, &raw);
+                process(transaction);
+            }
+            Err(_) => process(Err(ParseError)),
+        }
+    }
+}
+
+fn try_transaction_from<N: Number>(
+    headers: &StringRecord,
+    record: &StringRecord,
+    _raw: &RawRecord,
+) -> Result<Transaction<N>, ParseError> {
+    match record.deserialize::<TransactionRecord>(Some(headers)) {
+        Ok(rec) => Transaction::try_from(rec),
+        Err(_) => Err(ParseError),
+    }
+}
+
+#[derive(Deserialize)]
+struct TransactionRecord {
+    #[serde(rename = "type")]
+    trans_type: String,
+    client: u16,
+    tx: u32,
+    amount: Option<String>,
+}
+
+struct RawRecord {
+    line: u64,
+    byte: u64,
+    fields: Vec<String>,
+}
+
+impl From<&StringRecord> for RawRecord {
+    fn from(record: &StringRecord) -> Self {
+        let (line, byte) = record
+            .position()
+            .map_or((0, 0), |pos| (pos.line(), pos.byte()));
+        Self {
+            line,
+            byte,
+            fields: record.iter().map(String::from).collect(),
+        }
+    }
+}
+
+impl<N: Number> TryFrom<TransactionRecord> for Transaction<N> {
+    type Error = ParseError;
+
+    fn try_from(rec: TransactionRecord) -> Result<Self, Self::Error> {
+        let (client, referenced_tx) = (rec.client, rec.tx);
+        match rec.trans_type.as_str() {
+            "deposit" => Ok(Transaction::Deposit {
+                client,
+                tx: rec.tx,
+                amount: parse_amount(rec.amount)?,
+                disputed: false,
+            }),
+            "withdrawal" => Ok(Transaction::Withdrawal {
+                client,
+                tx: rec.tx,
+                amount: parse_amount(rec.amount)?,
+                disputed: false,
+            }),
+            "dispute" => Ok(Transaction::Dispute {
+                client,
+                referenced_tx,
+            }),
+            "resolve" => Ok(Transaction::Resolve {
+                client,
+                referenced_tx,
+            }),
+            "chargeback" => Ok(Transaction::ChargeBack {
+                client,
+                referenced_tx,
+            }),
+            _ => Err(ParseError),
+        }
+    }
+}
+
+fn parse_amount<N: Number>(amount: Option<String>) -> Result<N, ParseError> {
+    match amount {
+        Some(amount) => amount.parse::<N>().map_err(|_| ParseError),
+        None => Err(ParseError),
+    }
+}
@@ -0,0 +1,79 @@
+use std::{env, time::Instant};
+
+use bigdecimal::BigDecimal;
+use simple_xact::{amount::Amount, bank::Bank, io::read::processs_transactions_from_csv};
+
+mod baseline;
+
+fn main() {
+    let rows = env::var("BENCH_ROWS")
+        .ok()
+        .and_then(|rows| rows.parse().ok())
+        .unwrap_or(2_000_000);
+    let input = generate_input(rows);
+
+    let (bigdecimal, bigdecimal_applied) = measure("BigDecimal bank before Amount", rows, || {
+        run_baseline::<BigDecimal>(&input)
+    });
+    let (fixed, fixed_applied) = measure("Same bank with Amount", rows, || {
+        run_baseline::<Amount>(&input)
+    });
+    let (_, applied) = measure("Current bank end to end", rows, || {
+        let mut bank = Bank::default();
+        let mut applied = 0;
+        processs_transactions_from_csv(input.as_bytes(), |transaction| {
+            if let Ok(transaction) = transaction {
+                applied += bank.apply(transaction).is_ok() as usize;
+            }
+        });
+        applied
+    });
+    assert_eq!(bigdecimal_applied, fixed_applied);
+    assert_eq!(bigdecimal_applied, applied);
+    println!("Amount speedup over BigDecimal: {:.2}x", bigdecimal / fixed);
+}
+
+fn run_baseline<N: baseline::Number>(input: &str) -> usize {
+    let mut bank = baseline::Bank::<N>::default();
+    let mut applied = 0;
+    baseline::processs_transactions_from_csv(input.as_bytes(), |transaction| {
+        if let Ok(transaction) = transaction {
+            applied += bank.apply(transaction).is_ok() as usize;
+        }
+    });
+    applied
+}
+
+// Every client gets blocks of ten rows: six deposits, two withdrawals they
+// can cover, and a dispute and resolve of the first deposit of the block.
+fn generate_input(rows: usize) -> String {
+    let mut input = String::from("type,client,tx,amount\n");
+    for tx in 10..rows + 10 {
+        let block = tx - tx % 10;
+        let client = block / 10 % 1000 + 1;
+        let line = match tx % 10 {
+            0..=5 => format!(
+                "deposit,{client},{tx},{}.{:04}\n",
+                tx % 5000 + 100,
+                tx % 10_000
+            ),
+            6..=7 => format!("withdrawal,{client},{tx},{}.{:02}\n", tx % 100, tx % 100),
+            8 => format!("dispute,{client},{block},\n"),
+            _ => format!("resolve,{client},{block},\n"),
+        };
+        input.push_str(&line);
+    }
+    input
+}
+
+// The seconds a run took and the rows it applied.
+fn measure(name: &str, rows: usize, run: impl FnOnce() -> usize) -> (f64, usize) {
+    let start = Instant::now();
+    let applied = run();
+    let seconds = start.elapsed().as_secs_f64();
+    println!(
+        "{name}: {rows} rows in {seconds:.3}s ({:.0} rows/s, {applied} applied)",
+        rows as f64 / seconds
+    );
+    (seconds, applied)
+}
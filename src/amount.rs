@@ -0,0 +1,302 @@
+use std::{cmp::Ordering, error, fmt, str::FromStr};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Amount(i64);
+
+impl Amount {
+    pub const SCALE: u32 = 4;
+    pub const ZERO: Amount = Amount(0);
+    const FACTOR: i64 = 10_i64.pow(Self::SCALE);
+
+    pub fn from_scaled(scaled: i64) -> Self {
+        Self(scaled)
+    }
+
+    pub fn scaled(self) -> i64 {
+        self.0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn abs(self) -> Amount {
+        Amount(self.0.abs())
+    }
+
+    pub fn checked_add(self, rhs: Amount) -> Result<Amount, AmountError> {
+        self.0
+            .checked_add(rhs.0)
+            .map(Amount)
+            .ok_or(AmountError::Overflow)
+    }
+
+    pub fn checked_sub(self, rhs: Amount) -> Result<Amount, AmountError> {
+        self.0
+            .checked_sub(rhs.0)
+            .map(Amount)
+            .ok_or(AmountError::Overflow)
+    }
+
+    pub fn checked_neg(self) -> Result<Amount, AmountError> {
+        self.0
+            .checked_neg()
+            .map(Amount)
+            .ok_or(AmountError::Overflow)
+    }
+}
+
+impl FromStr for Amount {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PrecisionPolicy::default().parse(s)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_scale_string(Self::SCALE))
+    }
+}
+
+impl Amount {
+    pub fn round(self, scale: u32, mode: RoundingMode) -> Result<Amount, AmountError> {
+        let rounded = self.0.signum() as i128 * self.rounded_abs(scale, mode) as i128;
+        i64::try_from(rounded)
+            .map(Amount)
+            .map_err(|_| AmountError::Overflow)
+    }
+
+    // The absolute scaled amount rounded to scale decimal places, which can be
+    // one step beyond the largest amount.
+    fn rounded_abs(self, scale: u32, mode: RoundingMode) -> u64 {
+        let divisor = 10_u64.pow(Self::SCALE - scale.min(Self::SCALE));
+        let abs = self.0.unsigned_abs();
+        let (quotient, remainder) = (abs / divisor, abs % divisor);
+        match mode.rounds_up((remainder * 2).cmp(&divisor), quotient % 2 == 1) {
+            true => (quotient + 1) * divisor,
+            false => quotient * divisor,
+        }
+    }
+
+    // The given percentage of the amount, rounded to scale decimal places.
+    pub fn percent(
+        self,
+        rate: Amount,
+        scale: u32,
+        mode: RoundingMode,
+    ) -> Result<Amount, AmountError> {
+        self.prorate(rate, 1, 1, scale, mode)
+    }
+
+    // The given yearly percentage of the amount for days out of a year of
+    // year_days, rounded once to scale decimal places.
+    pub fn prorate(
+        self,
+        rate: Amount,
+        days: u32,
+        year_days: u32,
+        scale: u32,
+        mode: RoundingMode,
+    ) -> Result<Amount, AmountError> {
+        let step = 10_u128.pow(Self::SCALE - scale.min(Self::SCALE));
+        let divisor = Self::FACTOR as u128 * 100 * year_days as u128 * step;
+        let product = (self.0 as i128 * rate.0 as i128)
+            .checked_mul(days as i128)
+            .ok_or(AmountError::Overflow)?;
+        let abs = product.unsigned_abs();
+        let (quotient, remainder) = (abs / divisor, abs % divisor);
+        let rounded = match mode.rounds_up((remainder * 2).cmp(&divisor), quotient % 2 == 1) {
+            true => (quotient + 1) * step,
+            false => quotient * step,
+        };
+        i64::try_from(rounded)
+            .map(|rounded| Amount(product.signum() as i64 * rounded))
+            .map_err(|_| AmountError::Overflow)
+    }
+
+    fn to_scale_string(self, scale: u32) -> String {
+        scale_string(self.0 < 0, self.0.unsigned_abs(), scale)
+    }
+}
+
+fn scale_string(negative: bool, abs: u64, scale: u32) -> String {
+    let scale = scale.min(Amount::SCALE);
+    let sign = if negative && abs != 0 { "-" } else { "" };
+    let factor = Amount::FACTOR as u64;
+    match scale {
+        0 => format!("{sign}{}", abs / factor),
+        _ => format!(
+            "{sign}{}.{:0width$}",
+            abs / factor,
+            abs % factor / 10_u64.pow(Amount::SCALE - scale),
+            width = scale as usize
+        ),
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct PrecisionPolicy {
+    pub max_input_scale: u32,
+    pub excess: ExcessPrecision,
+    pub rounding: RoundingMode,
+    pub output_scale: u32,
+}
+
+impl Default for PrecisionPolicy {
+    fn default() -> Self {
+        Self {
+            max_input_scale: Amount::SCALE,
+            excess: ExcessPrecision::Reject,
+            rounding: RoundingMode::HalfEven,
+            output_scale: Amount::SCALE,
+        }
+    }
+}
+
+impl PrecisionPolicy {
+    pub fn parse(&self, s: &str) -> Result<Amount, AmountError> {
+        let (negative, digits) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (units, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if units.is_empty() && fraction.is_empty()
+            || !units
+                .bytes()
+                .chain(fraction.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return Err(AmountError::Invalid);
+        }
+        let max_scale = self.max_input_scale.min(Amount::SCALE) as usize;
+        let (kept, dropped) = fraction.split_at(fraction.len().min(max_scale));
+        let round_up = match dropped.trim_end_matches('0') {
+            "" => false,
+            _ if self.excess == ExcessPrecision::Reject => {
+                return Err(AmountError::ExcessPrecision)
+            }
+            dropped => {
+                let last = units.bytes().chain(kept.bytes()).last().unwrap_or(b'0');
+                let discarded = match dropped.as_bytes()[0].cmp(&b'5') {
+                    Ordering::Equal if dropped.len() > 1 => Ordering::Greater,
+                    discarded => discarded,
+                };
+                self.rounding.rounds_up(discarded, (last - b'0') % 2 == 1)
+            }
+        };
+        let scaled = units
+            .bytes()
+            .chain(kept.bytes())
+            .chain(std::iter::repeat_n(
+                b'0',
+                Amount::SCALE as usize - kept.len(),
+            ))
+            .try_fold(0_i64, |acc, digit| {
+                acc.checked_mul(10)?.checked_add((digit - b'0') as i64)
+            })
+            .ok_or(AmountError::Overflow)?;
+        let scaled = match round_up {
+            true => scaled
+                .checked_add(10_i64.pow(Amount::SCALE - max_scale as u32))
+                .ok_or(AmountError::Overflow)?,
+            false => scaled,
+        };
+        Ok(Amount(if negative { -scaled } else { scaled }))
+    }
+
+    pub fn apply(&self, amount: Amount) -> Result<Amount, AmountError> {
+        let max_scale = self.max_input_scale.min(Amount::SCALE);
+        match amount.round(max_scale, RoundingMode::Truncate)? == amount {
+            true => Ok(amount),
+            false if self.excess == ExcessPrecision::Reject => Err(AmountError::ExcessPrecision),
+            false => amount.round(max_scale, self.rounding),
+        }
+    }
+
+    // Written from the rounded absolute amount, so rounding up past the
+    // largest amount still prints it.
+    // An amount the bank computed, such as a fee, rounded to the input scale.
+    pub fn round(&self, amount: Amount) -> Result<Amount, AmountError> {
+        amount.round(self.max_input_scale, self.rounding)
+    }
+
+    pub fn format(&self, amount: Amount) -> String {
+        let abs = amount.rounded_abs(self.output_scale, self.rounding);
+        scale_string(amount.is_negative(), abs, self.output_scale)
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ExcessPrecision {
+    Reject,
+    Round,
+}
+
+impl FromStr for ExcessPrecision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reject" => Ok(ExcessPrecision::Reject),
+            "round" => Ok(ExcessPrecision::Round),
+            _ => Err(format!("Invalid excess precision handling: {s}")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum RoundingMode {
+    HalfEven,
+    HalfUp,
+    Truncate,
+}
+
+impl RoundingMode {
+    fn rounds_up(self, discarded: Ordering, odd: bool) -> bool {
+        match self {
+            RoundingMode::HalfEven => {
+                discarded == Ordering::Greater || discarded == Ordering::Equal && odd
+            }
+            RoundingMode::HalfUp => discarded != Ordering::Less,
+            RoundingMode::Truncate => false,
+        }
+    }
+}
+
+impl FromStr for RoundingMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "half-even" => Ok(RoundingMode::HalfEven),
+            "half-up" => Ok(RoundingMode::HalfUp),
+            "truncate" => Ok(RoundingMode::Truncate),
+            _ => Err(format!("Invalid rounding mode: {s}")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum AmountError {
+    Invalid,
+    ExcessPrecision,
+    Overflow,
+}
+
+impl fmt::Display for AmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmountError::Invalid => write!(f, "invalid amount"),
+            AmountError::ExcessPrecision => write!(f, "amount has too many decimal places"),
+            AmountError::Overflow => write!(f, "amount overflow"),
+        }
+    }
+}
+
+impl error::Error for AmountError {}
@@ -0,0 +1,232 @@
+use std::str::FromStr;
+
+use super::{Amount, AmountError, ExcessPrecision, PrecisionPolicy, RoundingMode};
+
+#[test]
+fn it_parses_amounts_with_up_to_four_decimal_places() {
+    // Arrange
+    let inputs = ["1", "1.5", "-1.5", "+0.0001", ".25", "7.", "6899.8000"];
+    let expected = [10_000, 15_000, -15_000, 1, 2_500, 70_000, 68_998_000];
+    // Act
+    let actual = inputs.map(|input| Amount::from_str(input).map(Amount::scaled));
+    // Assert
+    assert_eq!(expected.map(Ok), actual);
+}
+
+#[test]
+fn it_rejects_malformed_overprecise_and_overflowing_amounts() {
+    // Arrange
+    let inputs = [
+        "",
+        ".",
+        "-",
+        "1.x",
+        "1e3",
+        "1.2.3",
+        "0.00001",
+        "922337203685478",
+    ];
+    let expected = [
+        AmountError::Invalid,
+        AmountError::Invalid,
+        AmountError::Invalid,
+        AmountError::Invalid,
+        AmountError::Invalid,
+        AmountError::Invalid,
+        AmountError::ExcessPrecision,
+        AmountError::Overflow,
+    ];
+    // Act
+    let actual = inputs.map(Amount::from_str);
+    // Assert
+    assert_eq!(expected.map(Err), actual);
+}
+
+#[test]
+fn it_displays_amounts_with_four_decimal_places() {
+    // Arrange
+    let amounts = [0, 1, -1, 68_998_000, -15_000].map(Amount::from_scaled);
+    let expected = ["0.0000", "0.0001", "-0.0001", "6899.8000", "-1.5000"];
+    // Act
+    let actual = amounts.map(|amount| amount.to_string());
+    // Assert
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn it_reports_overflow_on_checked_arithmetic() {
+    // Arrange
+    let max = Amount::from_scaled(i64::MAX);
+    let min = Amount::from_scaled(i64::MIN);
+    let one = Amount::from_scaled(1);
+    // Act & Assert
+    assert_eq!(Err(AmountError::Overflow), max.checked_add(one));
+    assert_eq!(Err(AmountError::Overflow), min.checked_sub(one));
+    assert_eq!(Err(AmountError::Overflow), min.checked_neg());
+    assert_eq!(Ok(Amount::from_scaled(i64::MAX - 1)), max.checked_sub(one));
+}
+
+#[test]
+fn it_rounds_excess_input_precision_with_each_rounding_mode() {
+    // Arrange
+    let inputs = ["1.005", "1.015", "-1.005", "1.0051", "2.9999", "1.00000"];
+    let policy = |rounding| PrecisionPolicy {
+        max_input_scale: 2,
+        excess: ExcessPrecision::Round,
+        rounding,
+        output_scale: 4,
+    };
+    let expected = [
+        (
+            RoundingMode::HalfEven,
+            [10_000, 10_200, -10_000, 10_100, 30_000, 10_000],
+        ),
+        (
+            RoundingMode::HalfUp,
+            [10_100, 10_200, -10_100, 10_100, 30_000, 10_000],
+        ),
+        (
+            RoundingMode::Truncate,
+            [10_000, 10_100, -10_000, 10_000, 29_900, 10_000],
+        ),
+    ];
+    for (rounding, expected) in expected {
+        // Act
+        let actual = inputs.map(|input| policy(rounding).parse(input).map(Amount::scaled));
+        // Assert
+        assert_eq!(expected.map(Ok), actual, "{rounding:?}");
+    }
+}
+
+#[test]
+fn it_rejects_excess_input_precision_unless_only_trailing_zeros_exceed_it() {
+    // Arrange
+    let policy = PrecisionPolicy {
+        max_input_scale: 2,
+        ..PrecisionPolicy::default()
+    };
+    // Act & Assert
+    assert_eq!(Err(AmountError::ExcessPrecision), policy.parse("1.001"));
+    assert_eq!(Ok(Amount::from_scaled(12_000)), policy.parse("1.2000"));
+    assert_eq!(
+        Err(AmountError::ExcessPrecision),
+        policy.apply(Amount::from_scaled(10_010))
+    );
+    assert_eq!(
+        Ok(Amount::from_scaled(12_000)),
+        policy.apply(Amount::from_scaled(12_000))
+    );
+}
+
+#[test]
+fn it_rounds_past_the_largest_amount_without_wrapping() {
+    // Arrange
+    let largest = Amount::from_scaled(i64::MAX);
+    let policy = PrecisionPolicy {
+        max_input_scale: 0,
+        excess: ExcessPrecision::Round,
+        output_scale: 0,
+        ..PrecisionPolicy::default()
+    };
+    // Act & Assert
+    assert_eq!(
+        Err(AmountError::Overflow),
+        largest.round(0, RoundingMode::HalfEven)
+    );
+    assert_eq!(
+        Ok(Amount::from_scaled(i64::MAX - 5_807)),
+        largest.round(0, RoundingMode::Truncate)
+    );
+    assert_eq!(Err(AmountError::Overflow), policy.apply(largest));
+    assert_eq!("922337203685478", policy.format(largest));
+    assert_eq!(
+        "-922337203685478",
+        policy.format(Amount::from_scaled(-i64::MAX))
+    );
+}
+
+#[test]
+fn it_formats_amounts_with_a_fixed_output_scale() {
+    // Arrange
+    let amounts = [68_998_000, 5, 15, -12_345, 0].map(Amount::from_scaled);
+    let policy = |output_scale| PrecisionPolicy {
+        output_scale,
+        ..PrecisionPolicy::default()
+    };
+    // Act
+    let two = amounts.map(|amount| policy(2).format(amount));
+    let zero = amounts.map(|amount| policy(0).format(amount));
+    // Assert
+    assert_eq!(["6899.80", "0.00", "0.00", "-1.23", "0.00"], two);
+    assert_eq!(["6900", "0", "0", "-1", "0"], zero);
+}
+
+#[test]
+fn it_takes_a_percentage_of_an_amount_with_each_rounding_mode() {
+    // Arrange
+    let amount = Amount::from_str("10.0050").unwrap();
+    let rate = Amount::from_str("1.5").unwrap();
+    // Act
+    let actual = [
+        RoundingMode::HalfEven,
+        RoundingMode::HalfUp,
+        RoundingMode::Truncate,
+    ]
+    .map(|mode| amount.percent(rate, Amount::SCALE, mode).unwrap());
+    // Assert
+    assert_eq!(
+        [
+            Amount::from_str("0.1501").unwrap(),
+            Amount::from_str("0.1501").unwrap(),
+            Amount::from_str("0.1500").unwrap(),
+        ],
+        actual
+    );
+    assert_eq!(
+        Ok(Amount::from_str("-0.0002").unwrap()),
+        Amount::from_str("-0.0125")
+            .unwrap()
+            .percent(rate, Amount::SCALE, RoundingMode::HalfUp)
+    );
+    assert!(Amount::from_scaled(i64::MAX)
+        .percent(
+            Amount::from_str("200").unwrap(),
+            Amount::SCALE,
+            RoundingMode::HalfEven
+        )
+        .is_err());
+    assert_eq!(
+        Ok(Amount::from_str("0.15").unwrap()),
+        amount.percent(rate, 2, RoundingMode::HalfEven)
+    );
+}
+
+#[test]
+fn it_prorates_a_yearly_percentage_over_days_rounding_once() {
+    // Arrange
+    let amount = Amount::from_str("1000").unwrap();
+    let rate = Amount::from_str("5").unwrap();
+    // Act
+    let actual = [(30, 365), (30, 360), (0, 365)].map(|(days, year_days)| {
+        amount.prorate(rate, days, year_days, Amount::SCALE, RoundingMode::HalfEven)
+    });
+    // Assert
+    assert_eq!(
+        [
+            Amount::from_str("4.1096"),
+            Amount::from_str("4.1667"),
+            Ok(Amount::ZERO),
+        ],
+        actual
+    );
+    assert_eq!(
+        Amount::from_str("-15.2877"),
+        Amount::from_str("-1000").unwrap().prorate(
+            Amount::from_str("18").unwrap(),
+            31,
+            365,
+            Amount::SCALE,
+            RoundingMode::HalfUp
+        )
+    );
+}
@@ -1,262 +1,2293 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, error, fmt, str::FromStr};
 
-use bigdecimal::BigDecimal;
+use crate::{
+    amount::{Amount, AmountError, PrecisionPolicy},
+    date::Date,
+};
+
+use self::{
+    ledger::{Entry, Ledger, LedgerAccount, LedgerBalance},
+    policy::{DisputePolicy, FeeSchedule, FreezePolicy, InterestSchedule, ShortfallPolicy},
+};
+
+pub mod ledger;
+pub mod policy;
+pub mod verify;
 
 #[cfg(test)]
 mod tests;
 
-#[derive(Default)]
+#[derive(Default, Eq, PartialEq, Debug)]
 pub struct Bank {
-    accounts: HashMap<u16, (Account, HashMap<u32, Transaction>)>,
+    accounts: HashMap<u16, Account>,
+    ledger: Ledger,
+    transactions: HashMap<u32, Transaction>,
+    fees: HashMap<u32, Fee>,
+    authorizations: HashMap<u32, Authorization>,
+    closed_authorizations: HashMap<u32, u16>,
+    sequence: u64,
+    accrued_through: HashMap<u16, Date>,
+    history: HashMap<u16, Vec<HistoryEntry>>,
+    freeze_policy: FreezePolicy,
+    dispute_policy: DisputePolicy,
+    shortfall_policy: ShortfallPolicy,
+    precision: PrecisionPolicy,
+    flag_client_mismatch: bool,
+    fee_schedule: FeeSchedule,
+    authorization_expiry: Option<u64>,
+    interest_schedule: InterestSchedule,
 }
 
 impl Bank {
-    pub fn apply(&mut self, transaction: Transaction) {
-        match transaction {
+    pub fn with_freeze_policy(mut self, freeze_policy: FreezePolicy) -> Self {
+        self.freeze_policy = freeze_policy;
+        self
+    }
+
+    pub fn with_dispute_policy(mut self, dispute_policy: DisputePolicy) -> Self {
+        self.dispute_policy = dispute_policy;
+        self
+    }
+
+    pub fn with_shortfall_policy(mut self, shortfall_policy: ShortfallPolicy) -> Self {
+        self.shortfall_policy = shortfall_policy;
+        self
+    }
+
+    pub fn with_precision_policy(mut self, precision: PrecisionPolicy) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn with_client_mismatch_flagging(mut self, flag_client_mismatch: bool) -> Self {
+        self.flag_client_mismatch = flag_client_mismatch;
+        self
+    }
+
+    pub fn with_fee_schedule(mut self, fee_schedule: FeeSchedule) -> Self {
+        self.fee_schedule = fee_schedule;
+        self
+    }
+
+    pub fn with_authorization_expiry(mut self, authorization_expiry: Option<u64>) -> Self {
+        self.authorization_expiry = authorization_expiry;
+        self
+    }
+
+    pub fn with_interest_schedule(mut self, interest_schedule: InterestSchedule) -> Self {
+        self.interest_schedule = interest_schedule;
+        self
+    }
+
+    // Each accepted transaction journals one effect, followed by the fee it
+    // incurred, so the sequence counts the other effects. Only withdrawals,
+    // captures and chargebacks incur fees, and their tx stays reserved, so a
+    // fee right after one of them under its tx is part of the same row, while
+    // a fee after any other effect (such as an accrual or admin row, whose tx
+    // is not reserved) is a row of its own. Fails if a balance overflows.
+    pub fn replay(journal: impl IntoIterator<Item = AppliedEffect>) -> Result<Self, AmountError> {
+        let mut bank = Self::default();
+        let mut incurring = None;
+        for effect in journal {
+            bank.commit(&effect)?;
+            let advances = match effect {
+                AppliedEffect::Expired { .. } => false,
+                AppliedEffect::FeeCharged { tx, .. } | AppliedEffect::FeeRefunded { tx, .. } => {
+                    incurring != Some(tx)
+                }
+                _ => true,
+            };
+            if advances {
+                bank.sequence += 1;
+            }
+            incurring = match effect {
+                AppliedEffect::Debited { tx, .. }
+                | AppliedEffect::Captured { tx, .. }
+                | AppliedEffect::Reversed { tx, .. }
+                | AppliedEffect::PendingGranted { tx, .. } => Some(tx),
+                _ => None,
+            };
+        }
+        Ok(bank)
+    }
+
+    // Fails if the balances of the snapshot overflow the ledger.
+    pub fn restore(snapshot: Snapshot) -> Result<Self, AmountError> {
+        Ok(Self {
+            ledger: Ledger::open(&snapshot.accounts, &snapshot.ledger)?,
+            accounts: snapshot
+                .accounts
+                .into_iter()
+                .map(|acct| (acct.client, acct))
+                .collect(),
+            transactions: snapshot
+                .transactions
+                .into_iter()
+                .map(|transaction| (transaction.tx(), transaction))
+                .collect(),
+            fees: snapshot.fees.into_iter().map(|fee| (fee.tx, fee)).collect(),
+            authorizations: snapshot
+                .authorizations
+                .into_iter()
+                .map(|auth| (auth.tx, auth))
+                .collect(),
+            closed_authorizations: snapshot
+                .closed_authorizations
+                .into_iter()
+                .map(|closed| (closed.tx, closed.client))
+                .collect(),
+            sequence: snapshot.sequence,
+            accrued_through: snapshot
+                .accruals
+                .into_iter()
+                .map(|accrual| (accrual.client, accrual.as_of))
+                .collect(),
+            history: snapshot
+                .history
+                .into_iter()
+                .fold(HashMap::new(), |mut history, entry| {
+                    history.entry(entry.client).or_default().push(entry);
+                    history
+                }),
+            ..Self::default()
+        })
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        let mut accounts = self.accounts.values().cloned().collect::<Vec<_>>();
+        accounts.sort_by_key(|acct| acct.client);
+        let mut transactions = self.transactions.values().cloned().collect::<Vec<_>>();
+        transactions.sort_by_key(Transaction::tx);
+        let mut fees = self.fees.values().cloned().collect::<Vec<_>>();
+        fees.sort_by_key(|fee| fee.tx);
+        let mut authorizations = self.authorizations.values().cloned().collect::<Vec<_>>();
+        authorizations.sort_by_key(|auth| auth.tx);
+        let mut closed_authorizations = self
+            .closed_authorizations
+            .iter()
+            .map(|(&tx, &client)| ClosedAuthorization { client, tx })
+            .collect::<Vec<_>>();
+        closed_authorizations.sort_by_key(|closed| closed.tx);
+        let mut accruals = self
+            .accrued_through
+            .iter()
+            .map(|(&client, &as_of)| Accrual { client, as_of })
+            .collect::<Vec<_>>();
+        accruals.sort_by_key(|accrual| accrual.client);
+        let ledger = self
+            .ledger
+            .balances()
+            .into_iter()
+            .filter(|balance| balance.account.client().is_none())
+            .collect();
+        let mut clients = self.history.keys().copied().collect::<Vec<_>>();
+        clients.sort_unstable();
+        let history = clients
+            .into_iter()
+            .flat_map(|client| self.history(client).iter().cloned())
+            .collect();
+        Snapshot {
+            accounts,
+            transactions,
+            fees,
+            authorizations,
+            closed_authorizations,
+            sequence: self.sequence,
+            accruals,
+            ledger,
+            history,
+        }
+    }
+
+    // Partitions the bank by client into banks with the same policies. The
+    // first keeps the bank ledger accounts and the sequence, so merging the
+    // shards again adds up to the original bank.
+    pub fn split(self, shards: usize, shard_of: impl Fn(u16) -> usize) -> Vec<Bank> {
+        let mut ledger = self.ledger;
+        let mut ledgers = (1..shards)
+            .map(|shard| ledger.split_off(|account| account.client().map(&shard_of) == Some(shard)))
+            .collect::<Vec<_>>();
+        ledgers.insert(0, ledger);
+        let mut banks = ledgers
+            .into_iter()
+            .map(|ledger| Bank {
+                ledger,
+                freeze_policy: self.freeze_policy,
+                dispute_policy: self.dispute_policy,
+                shortfall_policy: self.shortfall_policy,
+                precision: self.precision,
+                flag_client_mismatch: self.flag_client_mismatch,
+                fee_schedule: self.fee_schedule,
+                authorization_expiry: self.authorization_expiry,
+                interest_schedule: self.interest_schedule.clone(),
+                ..Self::default()
+            })
+            .collect::<Vec<_>>();
+        banks[0].sequence = self.sequence;
+        for (client, acct) in self.accounts {
+            banks[shard_of(client)].accounts.insert(client, acct);
+        }
+        for (tx, transaction) in self.transactions {
+            banks[shard_of(transaction.client())]
+                .transactions
+                .insert(tx, transaction);
+        }
+        for (tx, fee) in self.fees {
+            banks[shard_of(fee.client)].fees.insert(tx, fee);
+        }
+        for (tx, auth) in self.authorizations {
+            banks[shard_of(auth.client)].authorizations.insert(tx, auth);
+        }
+        for (tx, client) in self.closed_authorizations {
+            banks[shard_of(client)]
+                .closed_authorizations
+                .insert(tx, client);
+        }
+        for (client, as_of) in self.accrued_through {
+            banks[shard_of(client)]
+                .accrued_through
+                .insert(client, as_of);
+        }
+        for (client, history) in self.history {
+            banks[shard_of(client)].history.insert(client, history);
+        }
+        banks
+    }
+
+    // Joins banks split by client back into one with the policies of the
+    // first, failing if the bank ledger accounts overflow together.
+    pub fn merge(shards: impl IntoIterator<Item = Bank>) -> Result<Bank, AmountError> {
+        let mut shards = shards.into_iter();
+        let mut bank = shards.next().unwrap_or_default();
+        for shard in shards {
+            bank.ledger.merge(shard.ledger)?;
+            bank.accounts.extend(shard.accounts);
+            bank.transactions.extend(shard.transactions);
+            bank.fees.extend(shard.fees);
+            bank.authorizations.extend(shard.authorizations);
+            bank.closed_authorizations
+                .extend(shard.closed_authorizations);
+            bank.sequence += shard.sequence;
+            bank.accrued_through.extend(shard.accrued_through);
+            bank.history.extend(shard.history);
+        }
+        Ok(bank)
+    }
+
+    pub fn apply(&mut self, transaction: Transaction) -> Result<AppliedEffect, Rejection> {
+        self.apply_with_fees(transaction)
+            .map(|mut effects| effects.swap_remove(0))
+    }
+
+    // Releases every authorization left uncaptured for more accepted
+    // transactions than the authorization expiry allows. Meant to be called
+    // before applying each transaction.
+    pub fn expire_authorizations(&mut self) -> Vec<AppliedEffect> {
+        let expiry = match self.authorization_expiry {
+            Some(expiry) => expiry,
+            None => return Vec::new(),
+        };
+        let mut expired = self
+            .authorizations
+            .values()
+            .filter(|auth| self.sequence.saturating_sub(auth.authorized_at) > expiry)
+            .map(|auth| (auth.tx, auth.client, auth.amount))
+            .collect::<Vec<_>>();
+        expired.sort();
+        expired
+            .into_iter()
+            .map(|(tx, client, amount)| AppliedEffect::Expired { client, tx, amount })
+            .filter(|effect| self.commit(effect).is_ok())
+            .collect()
+    }
+
+    // Accrues interest on every account as of a date, like an accrue row with
+    // tx 0 for each client. Closed accounts and those already accrued through
+    // that date are skipped.
+    pub fn accrue(&mut self, as_of: Date) -> Vec<AppliedEffect> {
+        let mut clients = self.accounts.keys().copied().collect::<Vec<_>>();
+        clients.sort();
+        clients
+            .into_iter()
+            .filter_map(|client| {
+                self.apply_with_fees(Transaction::Accrue {
+                    client,
+                    tx: 0,
+                    as_of,
+                })
+                .ok()
+            })
+            .flatten()
+            .collect()
+    }
+
+    // Like apply, but also returns the fee charged or refunded along with the
+    // transaction as a separate effect following its own.
+    pub fn apply_with_fees(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<Vec<AppliedEffect>, Rejection> {
+        self.check_not_frozen(&transaction)?;
+        let effect = match transaction {
             Transaction::Deposit {
                 client, tx, amount, ..
             } => self.apply_deposit(client, tx, amount),
             Transaction::Withdrawal {
                 client, tx, amount, ..
             } => self.appy_withdrawal(client, tx, amount),
+            Transaction::Transfer {
+                client,
+                to,
+                tx,
+                amount,
+                ..
+            } => self.apply_transfer(client, to, tx, amount),
             Transaction::Dispute {
                 client,
                 referenced_tx,
-                ..
-            } => self.apply_dispute(client, referenced_tx),
+                amount,
+            } => self.apply_dispute(client, referenced_tx, amount),
             Transaction::Resolve {
                 client,
                 referenced_tx,
-                ..
-            } => self.apply_resolve(client, referenced_tx),
+                amount,
+            } => self.apply_resolve(client, referenced_tx, amount),
             Transaction::ChargeBack {
                 client,
                 referenced_tx,
-                ..
-            } => self.apply_chargeback(client, referenced_tx),
+                amount,
+            } => self.apply_chargeback(client, referenced_tx, amount),
+            Transaction::Unlock { client, tx } => self.apply_unlock(client, tx),
+            Transaction::Close { client, tx } => self.apply_close(client, tx),
+            Transaction::Reopen { client, tx } => self.apply_reopen(client, tx),
+            Transaction::CreditLimit { client, tx, amount } => {
+                self.apply_credit_limit(client, tx, amount)
+            }
+            Transaction::Maintenance { client, tx } => self.apply_maintenance(client, tx),
+            Transaction::Authorize { client, tx, amount } => {
+                self.apply_authorize(client, tx, amount)
+            }
+            Transaction::Capture { client, tx, amount } => self.apply_capture(client, tx, amount),
+            Transaction::Void { client, tx } => self.apply_void(client, tx),
+            Transaction::Accrue { client, tx, as_of } => self.apply_accrue(client, tx, as_of),
+        }?;
+        let mut effects = vec![effect];
+        effects.extend(self.fee_effect(&effects[0])?);
+        self.check_no_overflow(&effects)?;
+        for effect in &effects {
+            self.commit(effect).map_err(|_| Rejection::Overflow {
+                client: effect.client(),
+                tx: effect.tx(),
+            })?;
         }
+        self.sequence += 1;
+        Ok(effects)
     }
 
     pub fn balances(&self) -> impl Iterator<Item = &Account> {
-        self.accounts.values().map(|(acct, _)| acct)
+        self.accounts.values()
     }
 
-    fn apply_deposit(&mut self, client: u16, tx: u32, amount: BigDecimal) {
-        self.accounts
-            .entry(client)
-            .and_modify(|(acct, transactions)| acct_deposit(acct, transactions, tx, &amount))
-            .or_insert_with(|| new_acct_deposit(client, tx, &amount));
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
     }
 
-    fn appy_withdrawal(&mut self, client: u16, tx: u32, amount: BigDecimal) {
-        self.accounts
-            .entry(client)
-            .and_modify(|(acct, transactions)| acct_withdrawal(acct, transactions, tx, amount));
+    // The effects applied to an account in order, each with the balances of
+    // the account right after it.
+    pub fn history(&self, client: u16) -> &[HistoryEntry] {
+        self.history.get(&client).map_or(&[], Vec::as_slice)
     }
 
-    fn apply_dispute(&mut self, client: u16, referenced_tx: u32) {
-        self.accounts
-            .entry(client)
-            .and_modify(|(acct, transactions)| acct_dispute(acct, transactions, referenced_tx));
+    pub fn transaction(&self, tx: u32) -> Option<&Transaction> {
+        self.transactions.get(&tx)
     }
 
-    fn apply_resolve(&mut self, client: u16, referenced_tx: u32) {
-        self.accounts
-            .entry(client)
-            .and_modify(|(acct, transactions)| acct_resolve(acct, transactions, referenced_tx));
+    // Every tx id in use with the clients it belongs to, which are the sender
+    // and the destination of a transfer and otherwise the same client twice.
+    pub fn tx_owners(&self) -> impl Iterator<Item = (u32, u16, u16)> + '_ {
+        let transactions = self.transactions.values().map(|transaction| {
+            let to = match *transaction {
+                Transaction::Transfer { to, .. } => to,
+                _ => transaction.client(),
+            };
+            (transaction.tx(), transaction.client(), to)
+        });
+        let fees = self
+            .fees
+            .values()
+            .map(|fee| (fee.tx, fee.client, fee.client));
+        let authorizations = self
+            .authorizations
+            .values()
+            .map(|auth| (auth.tx, auth.client, auth.client));
+        let closed_authorizations = self
+            .closed_authorizations
+            .iter()
+            .map(|(&tx, &client)| (tx, client, client));
+        transactions
+            .chain(fees)
+            .chain(authorizations)
+            .chain(closed_authorizations)
     }
 
-    fn apply_chargeback(&mut self, client: u16, referenced_tx: u32) {
-        self.accounts
-            .entry(client)
-            .and_modify(|(acct, transactions)| acct_chargeback(acct, transactions, referenced_tx));
+    pub fn authorization_expiry(&self) -> Option<u64> {
+        self.authorization_expiry
     }
-}
 
-fn acct_deposit(
-    acct: &mut Account,
-    transactions: &mut HashMap<u32, Transaction>,
-    tx: u32,
-    amount: &BigDecimal,
-) {
-    acct.available += amount;
-    transactions.insert(
-        tx,
-        Transaction::Deposit {
-            client: acct.client,
-            tx,
-            amount: amount.clone(),
-            disputed: false,
-        },
-    );
-}
+    fn apply_deposit(
+        &self,
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    ) -> Result<AppliedEffect, Rejection> {
+        self.check_unique(client, tx)?;
+        check_positive(client, tx, amount)?;
+        let amount = self.check_precision(client, tx, amount)?;
+        Ok(AppliedEffect::Credited { client, tx, amount })
+    }
 
-fn new_acct_deposit(
-    client: u16,
-    tx: u32,
-    amount: &BigDecimal,
-) -> (Account, HashMap<u32, Transaction>) {
-    (
-        Account {
+    fn appy_withdrawal(
+        &self,
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    ) -> Result<AppliedEffect, Rejection> {
+        self.check_unique(client, tx)?;
+        check_positive(client, tx, amount)?;
+        let amount = self.check_precision(client, tx, amount)?;
+        let fee = self.withdrawal_fee(client, tx, amount)?;
+        let charged = amount
+            .checked_add(fee)
+            .map_err(|_| Rejection::Overflow { client, tx })?;
+        self.check_funds(client, tx, charged)?;
+        Ok(AppliedEffect::Debited { client, tx, amount })
+    }
+
+    fn apply_transfer(
+        &self,
+        client: u16,
+        to: u16,
+        tx: u32,
+        amount: Amount,
+    ) -> Result<AppliedEffect, Rejection> {
+        self.check_unique(client, tx)?;
+        check_positive(client, tx, amount)?;
+        let amount = self.check_precision(client, tx, amount)?;
+        if client == to {
+            return Err(Rejection::SelfTransfer { client, tx });
+        }
+        self.check_receiving(to)?;
+        self.check_funds(client, tx, amount)?;
+        Ok(AppliedEffect::Transferred {
             client,
-            available: amount.clone(),
-            held: BigDecimal::default(),
-            locked: false,
-        },
-        HashMap::from([(
+            to,
             tx,
-            Transaction::Deposit {
+            amount,
+        })
+    }
+
+    fn apply_dispute(
+        &self,
+        client: u16,
+        referenced_tx: u32,
+        amount: Option<Amount>,
+    ) -> Result<AppliedEffect, Rejection> {
+        let disputable = self.disputable(client, referenced_tx)?;
+        if disputable.state.disputed().is_none() {
+            return Err(Rejection::AlreadyChargedBack {
                 client,
-                tx,
-                amount: amount.clone(),
-                disputed: false,
-            },
-        )]),
-    )
-}
+                referenced_tx,
+            });
+        }
+        if disputable.undisputed == Amount::ZERO {
+            return Err(Rejection::AlreadyDisputed {
+                client,
+                referenced_tx,
+            });
+        }
+        let portion = self.check_portion(client, referenced_tx, amount, disputable.undisputed)?;
+        let amount = disputable
+            .signed(portion)
+            .map_err(|_| Rejection::Overflow {
+                client,
+                tx: referenced_tx,
+            })?;
+        match amount.is_negative() {
+            true if self.dispute_policy == DisputePolicy::Disallow => {
+                Err(Rejection::WithdrawalDisputeDisallowed {
+                    client,
+                    referenced_tx,
+                })
+            }
+            _ if self.pends(amount) => Ok(AppliedEffect::PendingRaised {
+                client,
+                tx: referenced_tx,
+                amount: portion,
+            }),
+            _ => self.hold(disputable.holder, referenced_tx, amount),
+        }
+    }
 
-fn acct_withdrawal(
-    acct: &mut Account,
-    transactions: &mut HashMap<u32, Transaction>,
-    tx: u32,
-    amount: BigDecimal,
-) {
-    if acct.available >= amount {
-        acct.available -= &amount;
-        transactions.insert(
-            tx,
-            Transaction::Withdrawal {
-                client: acct.client,
-                tx,
-                amount,
-                disputed: false,
-            },
-        );
+    fn hold(
+        &self,
+        client: u16,
+        referenced_tx: u32,
+        amount: Amount,
+    ) -> Result<AppliedEffect, Rejection> {
+        let tx = referenced_tx;
+        match self.shortfall_policy {
+            _ if shortfall(self.existing_acct(client)?, amount)
+                .map_err(|_| Rejection::Overflow { client, tx })?
+                == Amount::ZERO =>
+            {
+                Ok(AppliedEffect::Held { client, tx, amount })
+            }
+            ShortfallPolicy::Reject => Err(Rejection::FundsAlreadyWithdrawn {
+                client,
+                referenced_tx,
+            }),
+            ShortfallPolicy::AllowNegative => Ok(AppliedEffect::Held { client, tx, amount }),
+            ShortfallPolicy::Flag => Ok(AppliedEffect::HeldFlagged { client, tx, amount }),
+            ShortfallPolicy::Receivable => {
+                Ok(AppliedEffect::HeldWithReceivable { client, tx, amount })
+            }
+        }
     }
-}
 
-fn acct_dispute(
-    acct: &mut Account,
-    transactions: &mut HashMap<u32, Transaction>,
-    referenced_tx: u32,
-) {
-    match transactions.get_mut(&referenced_tx) {
-        Some(Transaction::Deposit {
-            disputed,
-            ref amount,
-            ..
-        }) if !*disputed => {
-            *disputed = true;
-            acct.available -= amount;
-            acct.held += amount;
+    fn apply_resolve(
+        &self,
+        client: u16,
+        referenced_tx: u32,
+        amount: Option<Amount>,
+    ) -> Result<AppliedEffect, Rejection> {
+        let disputable = self.disputable(client, referenced_tx)?;
+        if disputable.state.resolved(disputable.disputed).is_none() {
+            return Err(not_disputed(disputable.state, client, referenced_tx));
         }
-        Some(Transaction::Withdrawal {
-            disputed,
-            ref amount,
-            ..
-        }) if !*disputed => {
-            *disputed = true;
-            acct.available += amount;
-            acct.held -= amount;
+        let portion = self.check_portion(client, referenced_tx, amount, disputable.disputed)?;
+        let amount = disputable
+            .signed(portion)
+            .map_err(|_| Rejection::Overflow {
+                client,
+                tx: referenced_tx,
+            })?;
+        match self.pends(amount) {
+            true => Ok(AppliedEffect::PendingDropped {
+                client,
+                tx: referenced_tx,
+                amount: portion,
+            }),
+            false => Ok(AppliedEffect::Released {
+                client: disputable.holder,
+                tx: referenced_tx,
+                amount,
+            }),
         }
-        _ => (),
-    };
-}
+    }
 
-fn acct_resolve(
-    acct: &mut Account,
-    transactions: &mut HashMap<u32, Transaction>,
-    referenced_tx: u32,
-) {
-    match transactions.get_mut(&referenced_tx) {
-        Some(Transaction::Deposit {
-            disputed,
-            ref amount,
-            ..
-        }) if *disputed => {
-            *disputed = false;
-            acct.available += amount;
-            acct.held -= amount;
+    fn apply_chargeback(
+        &self,
+        client: u16,
+        referenced_tx: u32,
+        amount: Option<Amount>,
+    ) -> Result<AppliedEffect, Rejection> {
+        let disputable = self.disputable(client, referenced_tx)?;
+        if disputable.state.charged_back(disputable.disputed).is_none() {
+            return Err(not_disputed(disputable.state, client, referenced_tx));
         }
-        Some(Transaction::Withdrawal {
-            disputed,
-            ref amount,
-            ..
-        }) if *disputed => {
-            *disputed = false;
-            acct.available -= amount;
-            acct.held += amount;
+        let portion = self.check_portion(client, referenced_tx, amount, disputable.disputed)?;
+        let amount = disputable
+            .signed(portion)
+            .map_err(|_| Rejection::Overflow {
+                client,
+                tx: referenced_tx,
+            })?;
+        match (self.pends(amount), disputable.sender) {
+            (true, _) => Ok(AppliedEffect::PendingGranted {
+                client,
+                tx: referenced_tx,
+                amount: portion,
+            }),
+            (false, Some(sender)) => Ok(AppliedEffect::Returned {
+                client: disputable.holder,
+                to: sender,
+                tx: referenced_tx,
+                amount,
+            }),
+            (false, None) => Ok(AppliedEffect::Reversed {
+                client,
+                tx: referenced_tx,
+                amount,
+            }),
         }
-        _ => (),
-    };
-}
+    }
 
-fn acct_chargeback(
-    acct: &mut Account,
-    transactions: &mut HashMap<u32, Transaction>,
-    referenced_tx: u32,
-) {
-    match transactions.get_mut(&referenced_tx) {
-        Some(Transaction::Deposit {
-            disputed,
-            ref amount,
-            ..
-        }) if *disputed => {
-            *disputed = false;
-            acct.held -= amount;
-            acct.locked = true;
+    fn apply_unlock(&self, client: u16, tx: u32) -> Result<AppliedEffect, Rejection> {
+        match self.existing_acct(client)?.status {
+            AccountStatus::Locked => Ok(AppliedEffect::Unlocked { client, tx }),
+            _ => Err(Rejection::NotLocked { client }),
         }
-        Some(Transaction::Withdrawal {
-            disputed,
-            ref amount,
-            ..
-        }) if *disputed => {
-            *disputed = false;
-            acct.held += amount;
-            acct.locked = true;
+    }
+
+    // A locked account is unlocked first, so reopening it cannot unlock it.
+    fn apply_close(&self, client: u16, tx: u32) -> Result<AppliedEffect, Rejection> {
+        let acct = self.existing_acct(client)?;
+        if acct.status == AccountStatus::Locked {
+            return Err(Rejection::AccountLocked { client });
         }
-        _ => (),
-    };
-}
+        match acct.available == Amount::ZERO
+            && acct.held == Amount::ZERO
+            && acct.pending_credit == Amount::ZERO
+            && acct.receivable == Amount::ZERO
+            && acct.authorized == Amount::ZERO
+        {
+            true => Ok(AppliedEffect::Closed { client, tx }),
+            false => Err(Rejection::NonZeroBalance { client }),
+        }
+    }
 
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
-pub enum Transaction {
-    Deposit {
+    fn apply_reopen(&self, client: u16, tx: u32) -> Result<AppliedEffect, Rejection> {
+        match self.existing_acct(client)?.status {
+            AccountStatus::Closed => Ok(AppliedEffect::Reopened { client, tx }),
+            _ => Err(Rejection::NotClosed { client }),
+        }
+    }
+
+    fn apply_credit_limit(
+        &self,
         client: u16,
         tx: u32,
-        amount: BigDecimal,
-        disputed: bool,
-    },
-    Withdrawal {
+        amount: Amount,
+    ) -> Result<AppliedEffect, Rejection> {
+        if amount.is_negative() {
+            return Err(Rejection::NegativeCreditLimit { client, tx });
+        }
+        let amount = self.check_precision(client, tx, amount)?;
+        Ok(AppliedEffect::CreditLimitSet { client, tx, amount })
+    }
+
+    fn apply_authorize(
+        &self,
         client: u16,
         tx: u32,
-        amount: BigDecimal,
-        disputed: bool,
-    },
-    Dispute {
+        amount: Amount,
+    ) -> Result<AppliedEffect, Rejection> {
+        self.check_unique(client, tx)?;
+        check_positive(client, tx, amount)?;
+        let amount = self.check_precision(client, tx, amount)?;
+        self.check_funds(client, tx, amount)?;
+        Ok(AppliedEffect::Authorized { client, tx, amount })
+    }
+
+    // A capture may differ from the authorized amount: the authorized funds are
+    // released and the captured amount is withdrawn, so it needs funds for any
+    // excess and is charged the withdrawal fee.
+    fn apply_capture(
+        &self,
         client: u16,
-        referenced_tx: u32,
-    },
-    Resolve {
+        tx: u32,
+        amount: Option<Amount>,
+    ) -> Result<AppliedEffect, Rejection> {
+        let authorized = self.open_authorization(client, tx)?.amount;
+        let amount = match amount {
+            Some(amount) => {
+                check_positive(client, tx, amount)?;
+                self.check_precision(client, tx, amount)?
+            }
+            None => authorized,
+        };
+        let charged = self
+            .withdrawal_fee(client, tx, amount)?
+            .checked_add(amount)
+            .and_then(|charged| charged.checked_sub(authorized))
+            .map_err(|_| Rejection::Overflow { client, tx })?;
+        self.check_funds(client, tx, charged)?;
+        Ok(AppliedEffect::Captured { client, tx, amount })
+    }
+
+    fn apply_void(&self, client: u16, tx: u32) -> Result<AppliedEffect, Rejection> {
+        let amount = self.open_authorization(client, tx)?.amount;
+        Ok(AppliedEffect::Voided { client, tx, amount })
+    }
+
+    // Interest is accrued on the available balance at the time of accrual over
+    // the days since the previous accrual of the account; the first accrual
+    // only starts that period and posts nothing.
+    fn apply_accrue(&self, client: u16, tx: u32, as_of: Date) -> Result<AppliedEffect, Rejection> {
+        let acct = self.existing_acct(client)?;
+        let amount = match self.accrued_through.get(&client) {
+            Some(&from) if from >= as_of => return Err(Rejection::StaleAccrual { client, tx }),
+            Some(&from) => self
+                .interest_schedule
+                .interest(acct.available, from, as_of, &self.precision)
+                .map_err(|_| Rejection::Overflow { client, tx })?,
+            None => Amount::ZERO,
+        };
+        Ok(AppliedEffect::InterestPosted {
+            client,
+            tx,
+            amount,
+            as_of,
+        })
+    }
+
+    fn apply_maintenance(&self, client: u16, tx: u32) -> Result<AppliedEffect, Rejection> {
+        self.check_unique(client, tx)?;
+        self.existing_acct(client)?;
+        match self.round_fee(client, tx, self.fee_schedule.maintenance)? {
+            amount if amount > Amount::ZERO => Ok(AppliedEffect::FeeCharged { client, tx, amount }),
+            _ => Err(Rejection::NoFee { client, tx }),
+        }
+    }
+
+    // A withdrawal is charged the withdrawal fee and a deposit chargeback the
+    // chargeback penalty. A withdrawal charged back in the client's favor
+    // refunds the fee that was charged for it.
+    fn fee_effect(&self, effect: &AppliedEffect) -> Result<Option<AppliedEffect>, Rejection> {
+        let (client, tx, amount) = match *effect {
+            AppliedEffect::Debited { client, tx, amount }
+            | AppliedEffect::Captured { client, tx, amount } => {
+                (client, tx, self.withdrawal_fee(client, tx, amount)?)
+            }
+            AppliedEffect::Reversed { client, tx, amount } if !amount.is_negative() => (
+                client,
+                tx,
+                self.round_fee(client, tx, self.fee_schedule.chargeback)?,
+            ),
+            AppliedEffect::Reversed { client, tx, .. }
+            | AppliedEffect::PendingGranted { client, tx, .. } => {
+                return Ok(self
+                    .fees
+                    .get(&tx)
+                    .filter(|fee| fee.amount > Amount::ZERO)
+                    .map(|fee| AppliedEffect::FeeRefunded {
+                        client,
+                        tx,
+                        amount: fee.amount,
+                    }))
+            }
+            _ => return Ok(None),
+        };
+        Ok((amount > Amount::ZERO).then_some(AppliedEffect::FeeCharged { client, tx, amount }))
+    }
+
+    fn withdrawal_fee(&self, client: u16, tx: u32, amount: Amount) -> Result<Amount, Rejection> {
+        self.fee_schedule
+            .withdrawal_fee(amount, &self.precision)
+            .map_err(|_| Rejection::Overflow { client, tx })
+    }
+
+    // Posts the ledger entries of an effect, updates the stored transactions
+    // and account state it changes, and derives the balances of the accounts
+    // it touches from the ledger.
+    fn commit(&mut self, effect: &AppliedEffect) -> Result<(), AmountError> {
+        let entries = self.entries(effect)?;
+        self.ledger.post(&entries)?;
+        let acct = self
+            .accounts
+            .entry(effect.client())
+            .or_insert_with(|| new_acct(effect.client()));
+        match effect {
+            AppliedEffect::Credited { tx, amount, .. } => {
+                acct_credit(acct, &mut self.transactions, *tx, *amount)
+            }
+            AppliedEffect::Debited { tx, amount, .. } => {
+                acct_debit(acct, &mut self.transactions, *tx, *amount)
+            }
+            AppliedEffect::Transferred { to, tx, amount, .. } => {
+                acct_transfer(acct, &mut self.transactions, *to, *tx, *amount)
+            }
+            AppliedEffect::Held { tx, amount, .. }
+            | AppliedEffect::HeldWithReceivable { tx, amount, .. } => {
+                tx_dispute(&mut self.transactions, *tx, amount.abs())?
+            }
+            AppliedEffect::HeldFlagged { tx, amount, .. } => {
+                acct_hold_flagged(acct, &mut self.transactions, *tx, *amount)?
+            }
+            AppliedEffect::Released { tx, amount, .. } => {
+                tx_resolve(&mut self.transactions, *tx, amount.abs())?
+            }
+            AppliedEffect::Reversed { tx, amount, .. } => {
+                acct_reverse(acct, &mut self.transactions, *tx, *amount)?
+            }
+            AppliedEffect::Returned { tx, amount, .. } => {
+                acct_reverse(acct, &mut self.transactions, *tx, *amount)?
+            }
+            AppliedEffect::PendingRaised { tx, amount, .. } => {
+                tx_dispute(&mut self.transactions, *tx, *amount)?
+            }
+            AppliedEffect::PendingDropped { tx, amount, .. } => {
+                tx_resolve(&mut self.transactions, *tx, *amount)?
+            }
+            AppliedEffect::PendingGranted { tx, amount, .. } => {
+                acct_grant_pending(acct, &mut self.transactions, *tx, *amount)?
+            }
+            AppliedEffect::Unlocked { .. } | AppliedEffect::Reopened { .. } => {
+                acct.status = AccountStatus::Active
+            }
+            AppliedEffect::Closed { .. } => acct.status = AccountStatus::Closed,
+            AppliedEffect::CreditLimitSet { amount, .. } => acct.credit_limit = *amount,
+            AppliedEffect::FeeCharged { tx, amount, .. } => {
+                acct_charge_fee(acct, &mut self.fees, *tx, *amount)?
+            }
+            AppliedEffect::FeeRefunded { tx, amount, .. } => {
+                acct_refund_fee(&mut self.fees, *tx, *amount)?
+            }
+            AppliedEffect::Authorized { tx, amount, .. } => {
+                self.authorizations.insert(
+                    *tx,
+                    Authorization {
+                        client: acct.client,
+                        tx: *tx,
+                        amount: *amount,
+                        authorized_at: self.sequence,
+                    },
+                );
+            }
+            AppliedEffect::Captured { tx, amount, .. } => {
+                self.authorizations.remove(tx);
+                acct_debit(acct, &mut self.transactions, *tx, *amount);
+            }
+            AppliedEffect::Voided { tx, .. } | AppliedEffect::Expired { tx, .. } => {
+                self.authorizations.remove(tx);
+                self.closed_authorizations.insert(*tx, acct.client);
+            }
+            AppliedEffect::InterestPosted { as_of, .. } => {
+                self.accrued_through.insert(acct.client, *as_of);
+            }
+        }
+        let mut clients = vec![effect.client()];
+        for client in entries
+            .iter()
+            .flat_map(|entry| [entry.debit, entry.credit])
+            .filter_map(LedgerAccount::client)
+        {
+            if !clients.contains(&client) {
+                clients.push(client);
+            }
+        }
+        for client in clients {
+            let acct = self
+                .accounts
+                .entry(client)
+                .or_insert_with(|| new_acct(client));
+            self.ledger.update_balances(acct)?;
+            self.history.entry(client).or_default().push(HistoryEntry {
+                client,
+                effect: effect.clone(),
+                available: acct.available,
+                held: acct.held,
+                total: acct.total()?,
+            });
+        }
+        Ok(())
+    }
+
+    // The balanced ledger entries of an effect. Deposits and withdrawals move
+    // funds between bank-cash and the client; a chargeback returns a deposit
+    // to bank-cash, while refunding a withdrawal is a chargeback loss.
+    fn entries(&self, effect: &AppliedEffect) -> Result<Vec<Entry>, AmountError> {
+        let entry = |debit, credit, amount| Entry {
+            debit,
+            credit,
+            amount,
+        };
+        let client = effect.client();
+        let acct = self
+            .accounts
+            .get(&client)
+            .cloned()
+            .unwrap_or_else(|| new_acct(client));
+        let entries = match *effect {
+            AppliedEffect::Credited { amount, .. } => vec![entry(
+                LedgerAccount::Cash,
+                LedgerAccount::Available(client),
+                amount,
+            )],
+            AppliedEffect::Debited { amount, .. } => vec![entry(
+                LedgerAccount::Available(client),
+                LedgerAccount::Cash,
+                amount,
+            )],
+            AppliedEffect::Transferred { to, amount, .. } => {
+                vec![entry(
+                    LedgerAccount::Available(client),
+                    LedgerAccount::Available(to),
+                    amount,
+                )]
+            }
+            AppliedEffect::Held { amount, .. } | AppliedEffect::HeldFlagged { amount, .. } => {
+                vec![entry(
+                    LedgerAccount::Available(client),
+                    LedgerAccount::Held(client),
+                    amount,
+                )]
+            }
+            AppliedEffect::HeldWithReceivable { amount, .. } => {
+                let shortfall = shortfall(&acct, amount)?;
+                vec![
+                    entry(
+                        LedgerAccount::Available(client),
+                        LedgerAccount::Held(client),
+                        amount.checked_sub(shortfall)?,
+                    ),
+                    entry(
+                        LedgerAccount::Receivable(client),
+                        LedgerAccount::Held(client),
+                        shortfall,
+                    ),
+                ]
+            }
+            AppliedEffect::Released { amount, .. } => {
+                let repaid = repaid(&acct, amount);
+                vec![
+                    entry(
+                        LedgerAccount::Held(client),
+                        LedgerAccount::Available(client),
+                        amount.checked_sub(repaid)?,
+                    ),
+                    entry(
+                        LedgerAccount::Held(client),
+                        LedgerAccount::Receivable(client),
+                        repaid,
+                    ),
+                ]
+            }
+            AppliedEffect::Reversed { amount, .. } if !amount.is_negative() => {
+                vec![entry(
+                    LedgerAccount::Held(client),
+                    LedgerAccount::Cash,
+                    amount,
+                )]
+            }
+            AppliedEffect::Reversed { amount, .. } => {
+                vec![entry(
+                    LedgerAccount::Held(client),
+                    LedgerAccount::ChargebackLoss,
+                    amount,
+                )]
+            }
+            AppliedEffect::Returned { to, amount, .. } => {
+                vec![entry(
+                    LedgerAccount::Held(client),
+                    LedgerAccount::Available(to),
+                    amount,
+                )]
+            }
+            AppliedEffect::PendingRaised { amount, .. } => {
+                vec![entry(
+                    LedgerAccount::ChargebackLoss,
+                    LedgerAccount::PendingCredit(client),
+                    amount,
+                )]
+            }
+            AppliedEffect::PendingDropped { amount, .. } => {
+                vec![entry(
+                    LedgerAccount::PendingCredit(client),
+                    LedgerAccount::ChargebackLoss,
+                    amount,
+                )]
+            }
+            AppliedEffect::PendingGranted { amount, .. } => {
+                vec![entry(
+                    LedgerAccount::PendingCredit(client),
+                    LedgerAccount::Available(client),
+                    amount,
+                )]
+            }
+            AppliedEffect::FeeCharged { amount, .. } => {
+                vec![entry(
+                    LedgerAccount::Available(client),
+                    LedgerAccount::FeeIncome,
+                    amount,
+                )]
+            }
+            AppliedEffect::FeeRefunded { amount, .. } => {
+                vec![entry(
+                    LedgerAccount::FeeIncome,
+                    LedgerAccount::Available(client),
+                    amount,
+                )]
+            }
+            AppliedEffect::Authorized { amount, .. } => {
+                vec![entry(
+                    LedgerAccount::Available(client),
+                    LedgerAccount::Authorized(client),
+                    amount,
+                )]
+            }
+            AppliedEffect::Captured { tx, amount, .. } => {
+                let authorized = self
+                    .authorizations
+                    .get(&tx)
+                    .map_or(Amount::ZERO, |auth| auth.amount);
+                vec![
+                    entry(
+                        LedgerAccount::Authorized(client),
+                        LedgerAccount::Available(client),
+                        authorized,
+                    ),
+                    entry(
+                        LedgerAccount::Available(client),
+                        LedgerAccount::Cash,
+                        amount,
+                    ),
+                ]
+            }
+            AppliedEffect::Voided { amount, .. } | AppliedEffect::Expired { amount, .. } => {
+                vec![entry(
+                    LedgerAccount::Authorized(client),
+                    LedgerAccount::Available(client),
+                    amount,
+                )]
+            }
+            AppliedEffect::InterestPosted { amount, .. } => {
+                vec![entry(
+                    LedgerAccount::InterestExpense,
+                    LedgerAccount::Available(client),
+                    amount,
+                )]
+            }
+            AppliedEffect::Unlocked { .. }
+            | AppliedEffect::Closed { .. }
+            | AppliedEffect::Reopened { .. }
+            | AppliedEffect::CreditLimitSet { .. } => Vec::new(),
+        };
+        Ok(entries)
+    }
+
+    // Disputes of a transfer move funds of both its clients, so neither may
+    // be frozen against them.
+    fn check_not_frozen(&self, transaction: &Transaction) -> Result<(), Rejection> {
+        let client = transaction.client();
+        self.check_status(client, transaction)?;
+        if let Transaction::Dispute { referenced_tx, .. }
+        | Transaction::Resolve { referenced_tx, .. }
+        | Transaction::ChargeBack { referenced_tx, .. } = *transaction
+        {
+            match self.transactions.get(&referenced_tx) {
+                Some(&Transaction::Transfer {
+                    client: sender, to, ..
+                }) if sender == client || to == client => {
+                    self.check_status(sender, transaction)?;
+                    self.check_status(to, transaction)?;
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn check_status(&self, client: u16, transaction: &Transaction) -> Result<(), Rejection> {
+        match self.accounts.get(&client).map(|acct| acct.status) {
+            Some(AccountStatus::Closed) if !matches!(transaction, Transaction::Reopen { .. }) => {
+                Err(Rejection::AccountClosed { client })
+            }
+            Some(AccountStatus::Locked)
+                if !transaction.is_admin() && !self.freeze_policy.allows(transaction) =>
+            {
+                Err(Rejection::AccountLocked { client })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_no_overflow(&self, effects: &[AppliedEffect]) -> Result<(), Rejection> {
+        let mut entries = Vec::new();
+        for effect in effects {
+            let overflow = |client| Rejection::Overflow {
+                client,
+                tx: effect.tx(),
+            };
+            entries.extend(
+                self.entries(effect)
+                    .map_err(|_| overflow(effect.client()))?,
+            );
+            self.ledger
+                .check(&entries)
+                .map_err(|account| overflow(account.client().unwrap_or_else(|| effect.client())))?;
+        }
+        Ok(())
+    }
+
+    // A locked account only receives transfers when the freeze policy allows
+    // deposits; a closed account never does.
+    fn check_receiving(&self, to: u16) -> Result<(), Rejection> {
+        match self.existing_acct(to)?.status {
+            AccountStatus::Closed => Err(Rejection::AccountClosed { client: to }),
+            AccountStatus::Locked if self.freeze_policy != FreezePolicy::AllowDeposits => {
+                Err(Rejection::AccountLocked { client: to })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Withdrawals and transfers may overdraw available funds up to the credit
+    // limit of the account.
+    fn check_funds(&self, client: u16, tx: u32, amount: Amount) -> Result<(), Rejection> {
+        let acct = self.existing_acct(client)?;
+        match acct.available.checked_add(acct.credit_limit) {
+            Ok(spendable) if spendable >= amount => Ok(()),
+            _ => Err(Rejection::InsufficientFunds { client, tx }),
+        }
+    }
+
+    fn round_fee(&self, client: u16, tx: u32, amount: Amount) -> Result<Amount, Rejection> {
+        self.precision
+            .round(amount)
+            .map_err(|_| Rejection::Overflow { client, tx })
+    }
+
+    fn check_precision(&self, client: u16, tx: u32, amount: Amount) -> Result<Amount, Rejection> {
+        self.precision
+            .apply(amount)
+            .map_err(|_| Rejection::ExcessPrecision { client, tx })
+    }
+
+    fn check_portion(
+        &self,
         client: u16,
         referenced_tx: u32,
-    },
-    ChargeBack {
+        amount: Option<Amount>,
+        limit: Amount,
+    ) -> Result<Amount, Rejection> {
+        let amount = match amount {
+            Some(amount) => amount,
+            None => return Ok(limit),
+        };
+        check_positive(client, referenced_tx, amount)?;
+        match self.check_precision(client, referenced_tx, amount)? {
+            amount if amount <= limit => Ok(amount),
+            _ => Err(Rejection::DisputeAmountExceeded {
+                client,
+                referenced_tx,
+            }),
+        }
+    }
+
+    fn check_unique(&self, client: u16, tx: u32) -> Result<(), Rejection> {
+        match self.transactions.contains_key(&tx)
+            || self.fees.contains_key(&tx)
+            || self.authorizations.contains_key(&tx)
+            || self.closed_authorizations.contains_key(&tx)
+        {
+            true => Err(Rejection::DuplicateTransaction { client, tx }),
+            false => Ok(()),
+        }
+    }
+
+    fn existing_acct(&self, client: u16) -> Result<&Account, Rejection> {
+        self.accounts
+            .get(&client)
+            .ok_or(Rejection::UnknownClient { client })
+    }
+
+    fn owned_transaction(
+        &self,
         client: u16,
         referenced_tx: u32,
-    },
-}
+    ) -> Result<&Transaction, Rejection> {
+        match self.transactions.get(&referenced_tx) {
+            Some(transaction) if transaction.involves(client) => Ok(transaction),
+            Some(transaction) => Err(Rejection::ClientMismatch {
+                client,
+                referenced_tx,
+                owner: transaction.client(),
+                suspected_fraud: self.flag_client_mismatch,
+            }),
+            None => Err(Rejection::UnknownTransaction {
+                client,
+                referenced_tx,
+            }),
+        }
+    }
 
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
-pub struct Account {
-    pub client: u16,
-    pub available: BigDecimal,
-    pub held: BigDecimal,
-    pub locked: bool,
-}
+    fn open_authorization(&self, client: u16, tx: u32) -> Result<&Authorization, Rejection> {
+        self.existing_acct(client)?;
+        match self.authorizations.get(&tx) {
+            Some(auth) if auth.client == client => Ok(auth),
+            _ => Err(Rejection::UnknownAuthorization { client, tx }),
+        }
+    }
 
-impl Account {
-    pub fn total(&self) -> BigDecimal {
-        &self.available + &self.held
+    fn pends(&self, amount: Amount) -> bool {
+        amount.is_negative() && self.dispute_policy == DisputePolicy::PendingCredit
+    }
+
+    fn disputable(&self, client: u16, referenced_tx: u32) -> Result<Disputable, Rejection> {
+        self.existing_acct(client)?;
+        let (withdrawal, holder, sender, state, amount, disputed, charged_back) =
+            match self.owned_transaction(client, referenced_tx)? {
+                Transaction::Deposit {
+                    state,
+                    amount,
+                    disputed,
+                    charged_back,
+                    ..
+                } => (false, client, None, state, amount, disputed, charged_back),
+                Transaction::Withdrawal {
+                    state,
+                    amount,
+                    disputed,
+                    charged_back,
+                    ..
+                } => (true, client, None, state, amount, disputed, charged_back),
+                Transaction::Transfer {
+                    client: sender,
+                    to,
+                    state,
+                    amount,
+                    disputed,
+                    charged_back,
+                    ..
+                } => (
+                    false,
+                    *to,
+                    Some(*sender),
+                    state,
+                    amount,
+                    disputed,
+                    charged_back,
+                ),
+                _ => {
+                    return Err(Rejection::UnknownTransaction {
+                        client,
+                        referenced_tx,
+                    })
+                }
+            };
+        Ok(Disputable {
+            state: *state,
+            withdrawal,
+            holder,
+            sender,
+            undisputed: (amount.checked_sub(*disputed))
+                .and_then(|undisputed| undisputed.checked_sub(*charged_back))
+                .map_err(|_| Rejection::Overflow {
+                    client,
+                    tx: referenced_tx,
+                })?,
+            disputed: *disputed,
+        })
     }
 }
+
+// A stored deposit, withdrawal or transfer referenced by a dispute, resolve or
+// chargeback, with the portions of its amount that can still be disputed and
+// that are currently disputed. The holder is the account whose funds are held,
+// which for a transfer is its destination; a chargeback returns them to the
+// sender.
+struct Disputable {
+    state: DisputeState,
+    withdrawal: bool,
+    holder: u16,
+    sender: Option<u16>,
+    undisputed: Amount,
+    disputed: Amount,
+}
+
+impl Disputable {
+    fn signed(&self, portion: Amount) -> Result<Amount, AmountError> {
+        match self.withdrawal {
+            true => portion.checked_neg(),
+            false => Ok(portion),
+        }
+    }
+}
+
+fn not_disputed(state: DisputeState, client: u16, referenced_tx: u32) -> Rejection {
+    match state {
+        DisputeState::ChargedBack => Rejection::AlreadyChargedBack {
+            client,
+            referenced_tx,
+        },
+        _ => Rejection::NotDisputed {
+            client,
+            referenced_tx,
+        },
+    }
+}
+
+fn check_positive(client: u16, tx: u32, amount: Amount) -> Result<(), Rejection> {
+    match amount > Amount::ZERO {
+        true => Ok(()),
+        false => Err(Rejection::NonPositiveAmount { client, tx }),
+    }
+}
+
+// The part of a disputed amount that is no longer covered by available funds.
+fn shortfall(acct: &Account, amount: Amount) -> Result<Amount, AmountError> {
+    match amount > acct.available.max(Amount::ZERO) {
+        true => amount.checked_sub(acct.available.max(Amount::ZERO)),
+        false => Ok(Amount::ZERO),
+    }
+}
+
+// The part of a released amount that pays back the receivable first.
+fn repaid(acct: &Account, amount: Amount) -> Amount {
+    acct.receivable.min(amount.max(Amount::ZERO))
+}
+
+fn new_acct(client: u16) -> Account {
+    Account {
+        client,
+        available: Amount::ZERO,
+        held: Amount::ZERO,
+        status: AccountStatus::Active,
+        pending_credit: Amount::ZERO,
+        flagged: false,
+        receivable: Amount::ZERO,
+        credit_limit: Amount::ZERO,
+        authorized: Amount::ZERO,
+    }
+}
+
+fn acct_credit(
+    acct: &mut Account,
+    transactions: &mut HashMap<u32, Transaction>,
+    tx: u32,
+    amount: Amount,
+) {
+    transactions.insert(
+        tx,
+        Transaction::Deposit {
+            client: acct.client,
+            tx,
+            amount,
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        },
+    );
+}
+
+fn acct_debit(
+    acct: &mut Account,
+    transactions: &mut HashMap<u32, Transaction>,
+    tx: u32,
+    amount: Amount,
+) {
+    transactions.insert(
+        tx,
+        Transaction::Withdrawal {
+            client: acct.client,
+            tx,
+            amount,
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        },
+    );
+}
+
+fn acct_transfer(
+    acct: &mut Account,
+    transactions: &mut HashMap<u32, Transaction>,
+    to: u16,
+    tx: u32,
+    amount: Amount,
+) {
+    transactions.insert(
+        tx,
+        Transaction::Transfer {
+            client: acct.client,
+            to,
+            tx,
+            amount,
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        },
+    );
+}
+
+fn acct_hold_flagged(
+    acct: &mut Account,
+    transactions: &mut HashMap<u32, Transaction>,
+    referenced_tx: u32,
+    amount: Amount,
+) -> Result<(), AmountError> {
+    tx_dispute(transactions, referenced_tx, amount.abs())?;
+    acct.flagged = true;
+    Ok(())
+}
+
+fn acct_reverse(
+    acct: &mut Account,
+    transactions: &mut HashMap<u32, Transaction>,
+    referenced_tx: u32,
+    amount: Amount,
+) -> Result<(), AmountError> {
+    acct.status = AccountStatus::Locked;
+    tx_charge_back(transactions, referenced_tx, amount.abs())
+}
+
+fn acct_grant_pending(
+    acct: &mut Account,
+    transactions: &mut HashMap<u32, Transaction>,
+    referenced_tx: u32,
+    amount: Amount,
+) -> Result<(), AmountError> {
+    acct.status = AccountStatus::Locked;
+    tx_charge_back(transactions, referenced_tx, amount)
+}
+
+fn acct_charge_fee(
+    acct: &mut Account,
+    fees: &mut HashMap<u32, Fee>,
+    tx: u32,
+    amount: Amount,
+) -> Result<(), AmountError> {
+    let fee = fees.entry(tx).or_insert(Fee {
+        client: acct.client,
+        tx,
+        amount: Amount::ZERO,
+    });
+    fee.amount = fee.amount.checked_add(amount)?;
+    Ok(())
+}
+
+fn acct_refund_fee(
+    fees: &mut HashMap<u32, Fee>,
+    tx: u32,
+    amount: Amount,
+) -> Result<(), AmountError> {
+    if let Some(fee) = fees.get_mut(&tx) {
+        fee.amount = fee.amount.checked_sub(amount)?;
+    }
+    Ok(())
+}
+
+fn tx_dispute(
+    transactions: &mut HashMap<u32, Transaction>,
+    referenced_tx: u32,
+    portion: Amount,
+) -> Result<(), AmountError> {
+    if let Some((state, disputed, _)) = disputable_mut(transactions, referenced_tx) {
+        *disputed = disputed.checked_add(portion)?;
+        *state = state.disputed().unwrap_or(*state);
+    }
+    Ok(())
+}
+
+fn tx_resolve(
+    transactions: &mut HashMap<u32, Transaction>,
+    referenced_tx: u32,
+    portion: Amount,
+) -> Result<(), AmountError> {
+    if let Some((state, disputed, _)) = disputable_mut(transactions, referenced_tx) {
+        *disputed = disputed.checked_sub(portion)?;
+        *state = state.resolved(*disputed).unwrap_or(*state);
+    }
+    Ok(())
+}
+
+fn tx_charge_back(
+    transactions: &mut HashMap<u32, Transaction>,
+    referenced_tx: u32,
+    portion: Amount,
+) -> Result<(), AmountError> {
+    if let Some((state, disputed, charged_back)) = disputable_mut(transactions, referenced_tx) {
+        *disputed = disputed.checked_sub(portion)?;
+        *charged_back = charged_back.checked_add(portion)?;
+        *state = state.charged_back(*disputed).unwrap_or(*state);
+    }
+    Ok(())
+}
+
+fn disputable_mut(
+    transactions: &mut HashMap<u32, Transaction>,
+    referenced_tx: u32,
+) -> Option<(&mut DisputeState, &mut Amount, &mut Amount)> {
+    match transactions.get_mut(&referenced_tx)? {
+        Transaction::Deposit {
+            state,
+            disputed,
+            charged_back,
+            ..
+        }
+        | Transaction::Withdrawal {
+            state,
+            disputed,
+            charged_back,
+            ..
+        }
+        | Transaction::Transfer {
+            state,
+            disputed,
+            charged_back,
+            ..
+        } => Some((state, disputed, charged_back)),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum Transaction {
+    Deposit {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+        state: DisputeState,
+        disputed: Amount,
+        charged_back: Amount,
+    },
+    Withdrawal {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+        state: DisputeState,
+        disputed: Amount,
+        charged_back: Amount,
+    },
+    Transfer {
+        client: u16,
+        to: u16,
+        tx: u32,
+        amount: Amount,
+        state: DisputeState,
+        disputed: Amount,
+        charged_back: Amount,
+    },
+    Dispute {
+        client: u16,
+        referenced_tx: u32,
+        amount: Option<Amount>,
+    },
+    Resolve {
+        client: u16,
+        referenced_tx: u32,
+        amount: Option<Amount>,
+    },
+    ChargeBack {
+        client: u16,
+        referenced_tx: u32,
+        amount: Option<Amount>,
+    },
+    Unlock {
+        client: u16,
+        tx: u32,
+    },
+    Close {
+        client: u16,
+        tx: u32,
+    },
+    Reopen {
+        client: u16,
+        tx: u32,
+    },
+    CreditLimit {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Maintenance {
+        client: u16,
+        tx: u32,
+    },
+    Authorize {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Capture {
+        client: u16,
+        tx: u32,
+        amount: Option<Amount>,
+    },
+    Void {
+        client: u16,
+        tx: u32,
+    },
+    Accrue {
+        client: u16,
+        tx: u32,
+        as_of: Date,
+    },
+}
+
+impl Transaction {
+    pub fn client(&self) -> u16 {
+        match *self {
+            Transaction::Deposit { client, .. }
+            | Transaction::Withdrawal { client, .. }
+            | Transaction::Transfer { client, .. }
+            | Transaction::Dispute { client, .. }
+            | Transaction::Resolve { client, .. }
+            | Transaction::ChargeBack { client, .. }
+            | Transaction::Unlock { client, .. }
+            | Transaction::Close { client, .. }
+            | Transaction::Reopen { client, .. }
+            | Transaction::CreditLimit { client, .. }
+            | Transaction::Maintenance { client, .. }
+            | Transaction::Authorize { client, .. }
+            | Transaction::Capture { client, .. }
+            | Transaction::Void { client, .. }
+            | Transaction::Accrue { client, .. } => client,
+        }
+    }
+
+    pub fn tx(&self) -> u32 {
+        match *self {
+            Transaction::Deposit { tx, .. }
+            | Transaction::Withdrawal { tx, .. }
+            | Transaction::Transfer { tx, .. }
+            | Transaction::Unlock { tx, .. }
+            | Transaction::Close { tx, .. }
+            | Transaction::Reopen { tx, .. }
+            | Transaction::CreditLimit { tx, .. }
+            | Transaction::Maintenance { tx, .. }
+            | Transaction::Authorize { tx, .. }
+            | Transaction::Capture { tx, .. }
+            | Transaction::Void { tx, .. }
+            | Transaction::Accrue { tx, .. } => tx,
+            Transaction::Dispute { referenced_tx, .. }
+            | Transaction::Resolve { referenced_tx, .. }
+            | Transaction::ChargeBack { referenced_tx, .. } => referenced_tx,
+        }
+    }
+
+    // Both the sender and the destination of a transfer may dispute it.
+    fn involves(&self, client: u16) -> bool {
+        match *self {
+            Transaction::Transfer { to, .. } if to == client => true,
+            _ => self.client() == client,
+        }
+    }
+
+    pub fn is_admin(&self) -> bool {
+        matches!(
+            self,
+            Transaction::Unlock { .. }
+                | Transaction::Close { .. }
+                | Transaction::Reopen { .. }
+                | Transaction::CreditLimit { .. }
+                | Transaction::Maintenance { .. }
+                | Transaction::Accrue { .. }
+        )
+    }
+}
+
+// Dispute lifecycle of a stored deposit or withdrawal. Disputes, resolves and
+// chargebacks may cover only part of its amount; a resolve or chargeback that
+// leaves a portion disputed keeps it Disputed. Once nothing is left disputed a
+// chargeback is final, so no amount can ever be charged back twice:
+//
+//   state       | dispute  | resolve            | chargeback
+//   ------------+----------+--------------------+------------------------
+//   Settled     | Disputed | -                  | -
+//   Disputed    | Disputed | Resolved/Disputed  | ChargedBack/Disputed
+//   Resolved    | Disputed | -                  | -
+//   ChargedBack | -        | -                  | -
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum DisputeState {
+    #[default]
+    Settled,
+    Disputed,
+    Resolved,
+    ChargedBack,
+}
+
+impl DisputeState {
+    pub fn disputed(self) -> Option<DisputeState> {
+        match self {
+            DisputeState::ChargedBack => None,
+            _ => Some(DisputeState::Disputed),
+        }
+    }
+
+    pub fn resolved(self, still_disputed: Amount) -> Option<DisputeState> {
+        match self {
+            DisputeState::Disputed if still_disputed > Amount::ZERO => Some(DisputeState::Disputed),
+            DisputeState::Disputed => Some(DisputeState::Resolved),
+            _ => None,
+        }
+    }
+
+    pub fn charged_back(self, still_disputed: Amount) -> Option<DisputeState> {
+        match self {
+            DisputeState::Disputed if still_disputed > Amount::ZERO => Some(DisputeState::Disputed),
+            DisputeState::Disputed => Some(DisputeState::ChargedBack),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisputeState::Settled => "settled",
+            DisputeState::Disputed => "disputed",
+            DisputeState::Resolved => "resolved",
+            DisputeState::ChargedBack => "charged-back",
+        }
+    }
+}
+
+impl FromStr for DisputeState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "settled" => Ok(DisputeState::Settled),
+            "disputed" => Ok(DisputeState::Disputed),
+            "resolved" => Ok(DisputeState::Resolved),
+            "charged-back" => Ok(DisputeState::ChargedBack),
+            _ => Err(format!("Invalid dispute state: {s}")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum AccountStatus {
+    #[default]
+    Active,
+    Locked,
+    Closed,
+}
+
+impl AccountStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Locked => "locked",
+            AccountStatus::Closed => "closed",
+        }
+    }
+}
+
+impl FromStr for AccountStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(AccountStatus::Active),
+            "locked" => Ok(AccountStatus::Locked),
+            "closed" => Ok(AccountStatus::Closed),
+            _ => Err(format!("Invalid account status: {s}")),
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub struct Account {
+    pub client: u16,
+    pub available: Amount,
+    pub held: Amount,
+    pub status: AccountStatus,
+    pub pending_credit: Amount,
+    pub flagged: bool,
+    pub receivable: Amount,
+    pub credit_limit: Amount,
+    pub authorized: Amount,
+}
+
+impl Account {
+    pub fn total(&self) -> Result<Amount, AmountError> {
+        (self.available.checked_add(self.held)).and_then(|total| total.checked_add(self.authorized))
+    }
+
+    // The part of the credit limit not yet used by a negative available balance.
+    pub fn remaining_credit(&self) -> Result<Amount, AmountError> {
+        (self
+            .credit_limit
+            .checked_add(self.available.min(Amount::ZERO)))
+        .map(|remaining| remaining.max(Amount::ZERO))
+    }
+}
+
+// The fees charged to a client for a transaction, net of refunds, keyed by
+// the tx of the withdrawal, chargeback or maintenance row that incurred them.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Fee {
+    pub client: u16,
+    pub tx: u32,
+    pub amount: Amount,
+}
+
+// Funds reserved by an authorization until it is captured, voided or expires.
+// Expiry counts the transactions the bank applied since authorized_at.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Authorization {
+    pub client: u16,
+    pub tx: u32,
+    pub amount: Amount,
+    pub authorized_at: u64,
+}
+
+// An authorization that was voided or expired, whose tx stays in use.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct ClosedAuthorization {
+    pub client: u16,
+    pub tx: u32,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Snapshot {
+    pub accounts: Vec<Account>,
+    pub transactions: Vec<Transaction>,
+    pub fees: Vec<Fee>,
+    pub authorizations: Vec<Authorization>,
+    pub closed_authorizations: Vec<ClosedAuthorization>,
+    pub sequence: u64,
+    pub accruals: Vec<Accrual>,
+    pub ledger: Vec<LedgerBalance>,
+    pub history: Vec<HistoryEntry>,
+}
+
+// An effect in the history of an account, which also lists transfers to it
+// under the effect of the sending client.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct HistoryEntry {
+    pub client: u16,
+    pub effect: AppliedEffect,
+    pub available: Amount,
+    pub held: Amount,
+    pub total: Amount,
+}
+
+// The date through which interest was last accrued on an account.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct Accrual {
+    pub client: u16,
+    pub as_of: Date,
+}
+
+// Amounts on Held, Released and Reversed are signed: a disputed withdrawal
+// moves a negative amount into held. Reversed also locks the account.
+// The Pending effects carry a disputed withdrawal held as a pending credit
+// instead; PendingGranted credits it to available and locks the account.
+// HeldFlagged and HeldWithReceivable hold a deposit whose funds were already
+// withdrawn, flagging the account or moving the shortfall into receivable.
+// Transferred debits the sender and credits the destination `to`; Returned
+// charges back a disputed transfer held on the destination, crediting the
+// sender `to` and locking the destination. FeeCharged and FeeRefunded debit
+// and credit a fee to available, keyed by the tx that incurred it. Authorized
+// moves funds from available to authorized; Voided and Expired move them back,
+// and Captured releases them and withdraws the captured amount instead.
+// InterestPosted credits interest to available, or debits it when negative,
+// and records the date interest was accrued through.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum AppliedEffect {
+    Credited {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Debited {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Transferred {
+        client: u16,
+        to: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Held {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    HeldFlagged {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    HeldWithReceivable {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Released {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Reversed {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Returned {
+        client: u16,
+        to: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    PendingRaised {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    PendingDropped {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    PendingGranted {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Unlocked {
+        client: u16,
+        tx: u32,
+    },
+    Closed {
+        client: u16,
+        tx: u32,
+    },
+    Reopened {
+        client: u16,
+        tx: u32,
+    },
+    CreditLimitSet {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    FeeCharged {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    FeeRefunded {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Authorized {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Captured {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Voided {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Expired {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    InterestPosted {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+        as_of: Date,
+    },
+}
+
+impl AppliedEffect {
+    pub fn client(&self) -> u16 {
+        match *self {
+            AppliedEffect::Credited { client, .. }
+            | AppliedEffect::Debited { client, .. }
+            | AppliedEffect::Transferred { client, .. }
+            | AppliedEffect::Held { client, .. }
+            | AppliedEffect::HeldFlagged { client, .. }
+            | AppliedEffect::HeldWithReceivable { client, .. }
+            | AppliedEffect::Released { client, .. }
+            | AppliedEffect::Reversed { client, .. }
+            | AppliedEffect::Returned { client, .. }
+            | AppliedEffect::PendingRaised { client, .. }
+            | AppliedEffect::PendingDropped { client, .. }
+            | AppliedEffect::PendingGranted { client, .. }
+            | AppliedEffect::Unlocked { client, .. }
+            | AppliedEffect::Closed { client, .. }
+            | AppliedEffect::Reopened { client, .. }
+            | AppliedEffect::CreditLimitSet { client, .. }
+            | AppliedEffect::FeeCharged { client, .. }
+            | AppliedEffect::FeeRefunded { client, .. }
+            | AppliedEffect::Authorized { client, .. }
+            | AppliedEffect::Captured { client, .. }
+            | AppliedEffect::Voided { client, .. }
+            | AppliedEffect::Expired { client, .. }
+            | AppliedEffect::InterestPosted { client, .. } => client,
+        }
+    }
+
+    pub fn tx(&self) -> u32 {
+        match *self {
+            AppliedEffect::Credited { tx, .. }
+            | AppliedEffect::Debited { tx, .. }
+            | AppliedEffect::Transferred { tx, .. }
+            | AppliedEffect::Held { tx, .. }
+            | AppliedEffect::HeldFlagged { tx, .. }
+            | AppliedEffect::HeldWithReceivable { tx, .. }
+            | AppliedEffect::Released { tx, .. }
+            | AppliedEffect::Reversed { tx, .. }
+            | AppliedEffect::Returned { tx, .. }
+            | AppliedEffect::PendingRaised { tx, .. }
+            | AppliedEffect::PendingDropped { tx, .. }
+            | AppliedEffect::PendingGranted { tx, .. }
+            | AppliedEffect::Unlocked { tx, .. }
+            | AppliedEffect::Closed { tx, .. }
+            | AppliedEffect::Reopened { tx, .. }
+            | AppliedEffect::CreditLimitSet { tx, .. }
+            | AppliedEffect::FeeCharged { tx, .. }
+            | AppliedEffect::FeeRefunded { tx, .. }
+            | AppliedEffect::Authorized { tx, .. }
+            | AppliedEffect::Captured { tx, .. }
+            | AppliedEffect::Voided { tx, .. }
+            | AppliedEffect::Expired { tx, .. }
+            | AppliedEffect::InterestPosted { tx, .. } => tx,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Rejection {
+    InsufficientFunds {
+        client: u16,
+        tx: u32,
+    },
+    UnknownClient {
+        client: u16,
+    },
+    UnknownTransaction {
+        client: u16,
+        referenced_tx: u32,
+    },
+    AlreadyDisputed {
+        client: u16,
+        referenced_tx: u32,
+    },
+    NotDisputed {
+        client: u16,
+        referenced_tx: u32,
+    },
+    AlreadyChargedBack {
+        client: u16,
+        referenced_tx: u32,
+    },
+    WithdrawalDisputeDisallowed {
+        client: u16,
+        referenced_tx: u32,
+    },
+    FundsAlreadyWithdrawn {
+        client: u16,
+        referenced_tx: u32,
+    },
+    DisputeAmountExceeded {
+        client: u16,
+        referenced_tx: u32,
+    },
+    AccountLocked {
+        client: u16,
+    },
+    AccountClosed {
+        client: u16,
+    },
+    NotLocked {
+        client: u16,
+    },
+    NotClosed {
+        client: u16,
+    },
+    NonZeroBalance {
+        client: u16,
+    },
+    SelfTransfer {
+        client: u16,
+        tx: u32,
+    },
+    NegativeCreditLimit {
+        client: u16,
+        tx: u32,
+    },
+    NoFee {
+        client: u16,
+        tx: u32,
+    },
+    UnknownAuthorization {
+        client: u16,
+        tx: u32,
+    },
+    StaleAccrual {
+        client: u16,
+        tx: u32,
+    },
+    DuplicateTransaction {
+        client: u16,
+        tx: u32,
+    },
+    Overflow {
+        client: u16,
+        tx: u32,
+    },
+    ExcessPrecision {
+        client: u16,
+        tx: u32,
+    },
+    NonPositiveAmount {
+        client: u16,
+        tx: u32,
+    },
+    ClientMismatch {
+        client: u16,
+        referenced_tx: u32,
+        owner: u16,
+        suspected_fraud: bool,
+    },
+}
+
+impl Rejection {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rejection::InsufficientFunds { .. } => "insufficient_funds",
+            Rejection::UnknownClient { .. } => "unknown_client",
+            Rejection::UnknownTransaction { .. } => "unknown_transaction",
+            Rejection::AlreadyDisputed { .. } => "already_disputed",
+            Rejection::NotDisputed { .. } => "not_disputed",
+            Rejection::AlreadyChargedBack { .. } => "already_charged_back",
+            Rejection::WithdrawalDisputeDisallowed { .. } => "withdrawal_dispute_disallowed",
+            Rejection::FundsAlreadyWithdrawn { .. } => "funds_already_withdrawn",
+            Rejection::DisputeAmountExceeded { .. } => "dispute_amount_exceeded",
+            Rejection::AccountLocked { .. } => "account_locked",
+            Rejection::AccountClosed { .. } => "account_closed",
+            Rejection::NotLocked { .. } => "not_locked",
+            Rejection::NotClosed { .. } => "not_closed",
+            Rejection::NonZeroBalance { .. } => "non_zero_balance",
+            Rejection::SelfTransfer { .. } => "self_transfer",
+            Rejection::NegativeCreditLimit { .. } => "negative_credit_limit",
+            Rejection::NoFee { .. } => "no_fee",
+            Rejection::UnknownAuthorization { .. } => "unknown_authorization",
+            Rejection::StaleAccrual { .. } => "stale_accrual",
+            Rejection::DuplicateTransaction { .. } => "duplicate_transaction",
+            Rejection::Overflow { .. } => "amount_overflow",
+            Rejection::ExcessPrecision { .. } => "excess_precision",
+            Rejection::NonPositiveAmount { .. } => "non_positive_amount",
+            Rejection::ClientMismatch {
+                suspected_fraud: false,
+                ..
+            } => "client_mismatch",
+            Rejection::ClientMismatch {
+                suspected_fraud: true,
+                ..
+            } => "suspected_fraud",
+        }
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::InsufficientFunds { client, tx } => {
+                write!(f, "client {client} has insufficient funds for tx {tx}")
+            }
+            Rejection::UnknownClient { client } => write!(f, "client {client} has no account"),
+            Rejection::UnknownTransaction {
+                client,
+                referenced_tx,
+            } => write!(f, "client {client} has no tx {referenced_tx}"),
+            Rejection::AlreadyDisputed {
+                client,
+                referenced_tx,
+            } => write!(
+                f,
+                "tx {referenced_tx} of client {client} is already disputed"
+            ),
+            Rejection::NotDisputed {
+                client,
+                referenced_tx,
+            } => write!(f, "tx {referenced_tx} of client {client} is not disputed"),
+            Rejection::AlreadyChargedBack {
+                client,
+                referenced_tx,
+            } => write!(
+                f,
+                "tx {referenced_tx} of client {client} has already been charged back"
+            ),
+            Rejection::WithdrawalDisputeDisallowed {
+                client,
+                referenced_tx,
+            } => write!(
+                f,
+                "tx {referenced_tx} of client {client} is a withdrawal and cannot be disputed"
+            ),
+            Rejection::FundsAlreadyWithdrawn {
+                client,
+                referenced_tx,
+            } => write!(
+                f,
+                "funds of tx {referenced_tx} of client {client} have already been withdrawn"
+            ),
+            Rejection::DisputeAmountExceeded {
+                client,
+                referenced_tx,
+            } => write!(
+                f,
+                "amount exceeds the disputable part of tx {referenced_tx} of client {client}"
+            ),
+            Rejection::AccountLocked { client } => {
+                write!(f, "account of client {client} is locked")
+            }
+            Rejection::AccountClosed { client } => {
+                write!(f, "account of client {client} is closed")
+            }
+            Rejection::NotLocked { client } => {
+                write!(f, "account of client {client} is not locked")
+            }
+            Rejection::NotClosed { client } => {
+                write!(f, "account of client {client} is not closed")
+            }
+            Rejection::NonZeroBalance { client } => {
+                write!(f, "account of client {client} still has a balance")
+            }
+            Rejection::SelfTransfer { client, tx } => {
+                write!(
+                    f,
+                    "tx {tx} of client {client} transfers to the same account"
+                )
+            }
+            Rejection::NegativeCreditLimit { client, tx } => {
+                write!(f, "tx {tx} of client {client} sets a negative credit limit")
+            }
+            Rejection::NoFee { client, tx } => {
+                write!(f, "tx {tx} of client {client} has no fee configured")
+            }
+            Rejection::UnknownAuthorization { client, tx } => {
+                write!(f, "client {client} has no open authorization {tx}")
+            }
+            Rejection::StaleAccrual { client, tx } => {
+                write!(
+                    f,
+                    "tx {tx} of client {client} does not accrue past its last accrual"
+                )
+            }
+            Rejection::DuplicateTransaction { client, tx } => {
+                write!(f, "tx {tx} of client {client} has already been applied")
+            }
+            Rejection::Overflow { client, tx } => {
+                write!(f, "tx {tx} would overflow the balance of client {client}")
+            }
+            Rejection::ExcessPrecision { client, tx } => {
+                write!(f, "tx {tx} of client {client} has too many decimal places")
+            }
+            Rejection::NonPositiveAmount { client, tx } => {
+                write!(
+                    f,
+                    "tx {tx} of client {client} has an amount that is not positive"
+                )
+            }
+            Rejection::ClientMismatch {
+                client,
+                referenced_tx,
+                owner,
+                ..
+            } => write!(
+                f,
+                "client {client} referenced tx {referenced_tx} which belongs to client {owner}"
+            ),
+        }
+    }
+}
+
+impl error::Error for Rejection {}
@@ -0,0 +1,266 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crate::amount::{Amount, AmountError};
+
+use super::Account;
+
+// The accounts of the double-entry ledger underneath client balances. The
+// client accounts hold what the bank owes each client, except receivable which
+// a client owes the bank; the bank accounts hold the other side of the entries.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum LedgerAccount {
+    Available(u16),
+    Held(u16),
+    PendingCredit(u16),
+    Receivable(u16),
+    Authorized(u16),
+    Cash,
+    ChargebackLoss,
+    FeeIncome,
+    InterestExpense,
+}
+
+impl LedgerAccount {
+    pub fn client(self) -> Option<u16> {
+        match self {
+            LedgerAccount::Available(client)
+            | LedgerAccount::Held(client)
+            | LedgerAccount::PendingCredit(client)
+            | LedgerAccount::Receivable(client)
+            | LedgerAccount::Authorized(client) => Some(client),
+            LedgerAccount::Cash
+            | LedgerAccount::ChargebackLoss
+            | LedgerAccount::FeeIncome
+            | LedgerAccount::InterestExpense => None,
+        }
+    }
+}
+
+impl fmt::Display for LedgerAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerAccount::Available(client) => write!(f, "client-available:{client}"),
+            LedgerAccount::Held(client) => write!(f, "client-held:{client}"),
+            LedgerAccount::PendingCredit(client) => write!(f, "client-pending-credit:{client}"),
+            LedgerAccount::Receivable(client) => write!(f, "client-receivable:{client}"),
+            LedgerAccount::Authorized(client) => write!(f, "client-authorized:{client}"),
+            LedgerAccount::Cash => write!(f, "bank-cash"),
+            LedgerAccount::ChargebackLoss => write!(f, "chargeback-loss"),
+            LedgerAccount::FeeIncome => write!(f, "fee-income"),
+            LedgerAccount::InterestExpense => write!(f, "interest-expense"),
+        }
+    }
+}
+
+impl FromStr for LedgerAccount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid ledger account: {s}");
+        match s.split_once(':') {
+            Some((kind, client)) => {
+                let client = client.parse().map_err(|_| invalid())?;
+                match kind {
+                    "client-available" => Ok(LedgerAccount::Available(client)),
+                    "client-held" => Ok(LedgerAccount::Held(client)),
+                    "client-pending-credit" => Ok(LedgerAccount::PendingCredit(client)),
+                    "client-receivable" => Ok(LedgerAccount::Receivable(client)),
+                    "client-authorized" => Ok(LedgerAccount::Authorized(client)),
+                    _ => Err(invalid()),
+                }
+            }
+            None => match s {
+                "bank-cash" => Ok(LedgerAccount::Cash),
+                "chargeback-loss" => Ok(LedgerAccount::ChargebackLoss),
+                "fee-income" => Ok(LedgerAccount::FeeIncome),
+                "interest-expense" => Ok(LedgerAccount::InterestExpense),
+                _ => Err(invalid()),
+            },
+        }
+    }
+}
+
+// A balanced entry debiting one account and crediting another by the same
+// amount. Amounts may be negative, which swaps the sides.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct Entry {
+    pub debit: LedgerAccount,
+    pub credit: LedgerAccount,
+    pub amount: Amount,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct TrialBalance {
+    pub debits: Amount,
+    pub credits: Amount,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct LedgerBalance {
+    pub account: LedgerAccount,
+    pub balance: Amount,
+}
+
+// Balances are debit-positive, so the client accounts other than receivable
+// normally carry negative balances. Zero balances are not kept.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Ledger {
+    balances: HashMap<LedgerAccount, Amount>,
+}
+
+impl Ledger {
+    pub fn balance(&self, account: LedgerAccount) -> Amount {
+        self.balances.get(&account).copied().unwrap_or_default()
+    }
+
+    pub fn balances(&self) -> Vec<LedgerBalance> {
+        let mut balances = self
+            .balances
+            .iter()
+            .map(|(&account, &balance)| LedgerBalance { account, balance })
+            .collect::<Vec<_>>();
+        balances.sort_by_key(|balance| balance.account);
+        balances
+    }
+
+    // The totals of the debit and of the credit balances, which are equal as
+    // long as every entry posted was balanced.
+    pub fn trial_balance(&self) -> Result<TrialBalance, AmountError> {
+        let mut trial_balance = TrialBalance::default();
+        for balance in self.balances.values() {
+            match balance.is_negative() {
+                true => trial_balance.credits = trial_balance.credits.checked_sub(*balance)?,
+                false => trial_balance.debits = trial_balance.debits.checked_add(*balance)?,
+            }
+        }
+        Ok(trial_balance)
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        matches!(self.trial_balance(), Ok(totals) if totals.debits == totals.credits)
+    }
+
+    // Fails with the first account whose balance, or whose client's total,
+    // would overflow by posting the entries.
+    pub fn check(&self, entries: &[Entry]) -> Result<(), LedgerAccount> {
+        let mut balances = HashMap::new();
+        for entry in entries {
+            let credit = entry.amount.checked_neg().map_err(|_| entry.credit)?;
+            for (account, amount) in [(entry.debit, entry.amount), (entry.credit, credit)] {
+                let balance = balances
+                    .entry(account)
+                    .or_insert_with(|| self.balance(account));
+                *balance = balance.checked_add(amount).map_err(|_| account)?;
+                balance.checked_neg().map_err(|_| account)?;
+            }
+        }
+        let balance = |account| match balances.get(&account) {
+            Some(&balance) => balance,
+            None => self.balance(account),
+        };
+        for &account in balances.keys() {
+            if let Some(client) = account.client() {
+                balance(LedgerAccount::Available(client))
+                    .checked_add(balance(LedgerAccount::Held(client)))
+                    .and_then(|total| total.checked_add(balance(LedgerAccount::Authorized(client))))
+                    .and_then(Amount::checked_neg)
+                    .map_err(|_| account)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Fails if a balance overflows, having posted the entries before it.
+    pub fn post(&mut self, entries: &[Entry]) -> Result<(), AmountError> {
+        for entry in entries {
+            self.add(entry.debit, entry.amount)?;
+            self.add(entry.credit, entry.amount.checked_neg()?)?;
+        }
+        Ok(())
+    }
+
+    // Opens the ledger with the balances of restored accounts and bank
+    // accounts. Without any bank balances, bank-cash takes the other side.
+    pub fn open(
+        accounts: &[Account],
+        bank_balances: &[LedgerBalance],
+    ) -> Result<Self, AmountError> {
+        let mut ledger = Self::default();
+        for acct in accounts {
+            let client = acct.client;
+            ledger.add(
+                LedgerAccount::Available(client),
+                acct.available.checked_neg()?,
+            )?;
+            ledger.add(LedgerAccount::Held(client), acct.held.checked_neg()?)?;
+            ledger.add(
+                LedgerAccount::PendingCredit(client),
+                acct.pending_credit.checked_neg()?,
+            )?;
+            ledger.add(LedgerAccount::Receivable(client), acct.receivable)?;
+            ledger.add(
+                LedgerAccount::Authorized(client),
+                acct.authorized.checked_neg()?,
+            )?;
+        }
+        match bank_balances {
+            [] => {
+                let totals = ledger.trial_balance()?;
+                ledger.add(
+                    LedgerAccount::Cash,
+                    totals.credits.checked_sub(totals.debits)?,
+                )?;
+            }
+            balances => {
+                for balance in balances {
+                    ledger.add(balance.account, balance.balance)?;
+                }
+            }
+        }
+        Ok(ledger)
+    }
+
+    // Moves the balances of the accounts matching the predicate into a new
+    // ledger, which only balances together with the rest.
+    pub fn split_off(&mut self, moves: impl Fn(LedgerAccount) -> bool) -> Ledger {
+        let (moved, kept) = self
+            .balances
+            .drain()
+            .partition(|(account, _)| moves(*account));
+        self.balances = kept;
+        Ledger { balances: moved }
+    }
+
+    pub fn merge(&mut self, other: Ledger) -> Result<(), AmountError> {
+        for (account, amount) in other.balances {
+            self.add(account, amount)?;
+        }
+        Ok(())
+    }
+
+    // Sets the balances of an account to those of its client accounts.
+    pub fn update_balances(&self, acct: &mut Account) -> Result<(), AmountError> {
+        let client = acct.client;
+        acct.available = self
+            .balance(LedgerAccount::Available(client))
+            .checked_neg()?;
+        acct.held = self.balance(LedgerAccount::Held(client)).checked_neg()?;
+        acct.pending_credit = self
+            .balance(LedgerAccount::PendingCredit(client))
+            .checked_neg()?;
+        acct.receivable = self.balance(LedgerAccount::Receivable(client));
+        acct.authorized = self
+            .balance(LedgerAccount::Authorized(client))
+            .checked_neg()?;
+        Ok(())
+    }
+
+    fn add(&mut self, account: LedgerAccount, amount: Amount) -> Result<(), AmountError> {
+        let balance = self.balance(account).checked_add(amount)?;
+        match balance == Amount::ZERO {
+            true => self.balances.remove(&account),
+            false => self.balances.insert(account, balance),
+        };
+        Ok(())
+    }
+}
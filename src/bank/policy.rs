@@ -0,0 +1,207 @@
+use std::str::FromStr;
+
+use crate::{
+    amount::{Amount, AmountError, PrecisionPolicy},
+    date::Date,
+};
+
+use super::Transaction;
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum FreezePolicy {
+    #[default]
+    RejectAll,
+    AllowDisputes,
+    AllowDeposits,
+}
+
+impl FreezePolicy {
+    pub fn allows(&self, transaction: &Transaction) -> bool {
+        match self {
+            FreezePolicy::RejectAll => false,
+            FreezePolicy::AllowDisputes => matches!(
+                transaction,
+                Transaction::Dispute { .. } | Transaction::Resolve { .. }
+            ),
+            FreezePolicy::AllowDeposits => matches!(transaction, Transaction::Deposit { .. }),
+        }
+    }
+}
+
+impl FromStr for FreezePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reject-all" => Ok(FreezePolicy::RejectAll),
+            "allow-disputes" => Ok(FreezePolicy::AllowDisputes),
+            "allow-deposits" => Ok(FreezePolicy::AllowDeposits),
+            _ => Err(format!("Invalid freeze policy: {s}")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum ShortfallPolicy {
+    Reject,
+    #[default]
+    AllowNegative,
+    Flag,
+    Receivable,
+}
+
+impl FromStr for ShortfallPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reject" => Ok(ShortfallPolicy::Reject),
+            "allow-negative" => Ok(ShortfallPolicy::AllowNegative),
+            "flag" => Ok(ShortfallPolicy::Flag),
+            "receivable" => Ok(ShortfallPolicy::Receivable),
+            _ => Err(format!("Invalid shortfall policy: {s}")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum DisputePolicy {
+    Disallow,
+    #[default]
+    RefundToAvailable,
+    PendingCredit,
+}
+
+impl FromStr for DisputePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disallow" => Ok(DisputePolicy::Disallow),
+            "refund-to-available" => Ok(DisputePolicy::RefundToAvailable),
+            "pending-credit" => Ok(DisputePolicy::PendingCredit),
+            _ => Err(format!("Invalid dispute policy: {s}")),
+        }
+    }
+}
+
+// Fees charged by the bank: a flat amount plus a percentage of every
+// withdrawal, a penalty for every chargeback of a deposit and a maintenance
+// fee charged by a `maintenance` row. All default to zero.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct FeeSchedule {
+    pub withdrawal_flat: Amount,
+    pub withdrawal_percent: Amount,
+    pub chargeback: Amount,
+    pub maintenance: Amount,
+}
+
+impl FeeSchedule {
+    // Rounded to the input scale of the precision policy, like every fee.
+    pub fn withdrawal_fee(
+        &self,
+        amount: Amount,
+        precision: &PrecisionPolicy,
+    ) -> Result<Amount, AmountError> {
+        amount
+            .percent(
+                self.withdrawal_percent,
+                precision.max_input_scale,
+                precision.rounding,
+            )?
+            .checked_add(precision.round(self.withdrawal_flat)?)
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum DayCount {
+    Actual360,
+    #[default]
+    Actual365,
+    Thirty360,
+}
+
+impl DayCount {
+    // The days accrued from one date to a later one and the days in a year.
+    pub fn days(self, from: Date, to: Date) -> (i64, u32) {
+        match self {
+            DayCount::Actual360 => (to.days_since_epoch() - from.days_since_epoch(), 360),
+            DayCount::Actual365 => (to.days_since_epoch() - from.days_since_epoch(), 365),
+            DayCount::Thirty360 => {
+                let from_day = from.day().min(30);
+                let to_day = match from_day {
+                    30 => to.day().min(30),
+                    _ => to.day(),
+                };
+                let years = (to.year() - from.year()) as i64;
+                let months = to.month() as i64 - from.month() as i64;
+                let days = to_day as i64 - from_day as i64;
+                (years * 360 + months * 30 + days, 360)
+            }
+        }
+    }
+}
+
+impl FromStr for DayCount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "actual-360" => Ok(DayCount::Actual360),
+            "actual-365" => Ok(DayCount::Actual365),
+            "30-360" => Ok(DayCount::Thirty360),
+            _ => Err(format!("Invalid day count convention: {s}")),
+        }
+    }
+}
+
+// Yearly interest rates by balance tier: credit tiers pay interest on a
+// positive available balance and debit tiers charge it on an overdrawn one.
+// The tier with the highest threshold reached applies to the whole balance.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct InterestSchedule {
+    pub day_count: DayCount,
+    pub credit: Vec<RateTier>,
+    pub debit: Vec<RateTier>,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct RateTier {
+    pub from: Amount,
+    pub rate: Amount,
+}
+
+impl InterestSchedule {
+    pub fn rate(&self, balance: Amount) -> Amount {
+        let tiers = match balance.is_negative() {
+            true => &self.debit,
+            false => &self.credit,
+        };
+        tiers
+            .iter()
+            .filter(|tier| tier.from <= balance.abs())
+            .max_by_key(|tier| tier.from)
+            .map_or(Amount::ZERO, |tier| tier.rate)
+    }
+
+    // The interest on a balance from one date to a later one, negative when
+    // it is charged on an overdrawn balance, rounded once to the input scale
+    // of the precision policy.
+    pub fn interest(
+        &self,
+        balance: Amount,
+        from: Date,
+        to: Date,
+        precision: &PrecisionPolicy,
+    ) -> Result<Amount, AmountError> {
+        let (days, year_days) = self.day_count.days(from, to);
+        let days = u32::try_from(days).map_err(|_| AmountError::Overflow)?;
+        balance.prorate(
+            self.rate(balance),
+            days,
+            year_days,
+            precision.max_input_scale,
+            precision.rounding,
+        )
+    }
+}
@@ -1,8 +1,20 @@
 use std::str::FromStr;
 
-use bigdecimal::BigDecimal;
+use crate::{
+    amount::{Amount, AmountError, ExcessPrecision, PrecisionPolicy},
+    date::Date,
+};
 
-use super::{Account, Bank, Transaction};
+use super::{
+    ledger::{LedgerAccount, LedgerBalance, TrialBalance},
+    policy::{
+        DayCount, DisputePolicy, FeeSchedule, FreezePolicy, InterestSchedule, RateTier,
+        ShortfallPolicy,
+    },
+    verify::Violation,
+    Account, AccountStatus, AppliedEffect, Authorization, Bank, ClosedAuthorization, DisputeState,
+    Fee, Rejection, Snapshot, Transaction,
+};
 
 #[test]
 fn it_handles_deposits() {
@@ -12,15 +24,25 @@ fn it_handles_deposits() {
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("672.6200").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("672.6200").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
@@ -39,21 +61,34 @@ fn it_handles_a_withdrawal() {
     bank.apply(Transaction::Withdrawal {
         client: 2,
         tx: 2000,
-        amount: BigDecimal::from_str("72.6052").unwrap(),
-        disputed: false,
-    });
+        amount: Amount::from_str("72.6052").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("600.0148").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("600.0148").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
@@ -72,19 +107,31 @@ fn it_handles_a_dispute_of_a_deposit() {
     bank.apply(Transaction::Dispute {
         client: 2,
         referenced_tx: 200,
-    });
+        amount: None,
+    })
+    .unwrap();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("200.0022").unwrap(),
-            held: BigDecimal::from_str("472.6178").unwrap(),
-            locked: false,
+            available: Amount::from_str("200.0022").unwrap(),
+            held: Amount::from_str("472.6178").unwrap(),
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
@@ -103,25 +150,40 @@ fn it_handles_a_dispute_of_a_withdrawal() {
     bank.apply(Transaction::Withdrawal {
         client: 2,
         tx: 2000,
-        amount: BigDecimal::from_str("72.6052").unwrap(),
-        disputed: false,
-    });
+        amount: Amount::from_str("72.6052").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
     bank.apply(Transaction::Dispute {
         client: 2,
         referenced_tx: 2000,
-    });
+        amount: None,
+    })
+    .unwrap();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("672.62").unwrap(),
-            held: BigDecimal::from_str("-72.6052").unwrap(),
-            locked: false,
+            available: Amount::from_str("672.62").unwrap(),
+            held: Amount::from_str("-72.6052").unwrap(),
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
@@ -140,23 +202,37 @@ fn it_handles_a_resolve_of_a_disputed_deposit() {
     bank.apply(Transaction::Dispute {
         client: 2,
         referenced_tx: 200,
-    });
+        amount: None,
+    })
+    .unwrap();
     bank.apply(Transaction::Resolve {
         client: 2,
         referenced_tx: 200,
-    });
+        amount: None,
+    })
+    .unwrap();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("672.62").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("672.62").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
@@ -175,29 +251,46 @@ fn it_handles_a_resolve_of_a_disputed_withdrawal() {
     bank.apply(Transaction::Withdrawal {
         client: 2,
         tx: 2000,
-        amount: BigDecimal::from_str("72.6052").unwrap(),
-        disputed: false,
-    });
+        amount: Amount::from_str("72.6052").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
     bank.apply(Transaction::Dispute {
         client: 2,
         referenced_tx: 2000,
-    });
+        amount: None,
+    })
+    .unwrap();
     bank.apply(Transaction::Resolve {
         client: 2,
         referenced_tx: 2000,
-    });
+        amount: None,
+    })
+    .unwrap();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("600.0148").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("600.0148").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
@@ -216,23 +309,37 @@ fn it_handles_a_chargeback_of_a_disputed_deposit() {
     bank.apply(Transaction::Dispute {
         client: 2,
         referenced_tx: 200,
-    });
+        amount: None,
+    })
+    .unwrap();
     bank.apply(Transaction::ChargeBack {
         client: 2,
         referenced_tx: 200,
-    });
+        amount: None,
+    })
+    .unwrap();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("200.0022").unwrap(),
-            held: BigDecimal::default(),
-            locked: true,
+            available: Amount::from_str("200.0022").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Locked,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
@@ -251,29 +358,46 @@ fn it_handles_a_chargeback_of_a_disputed_withdrawal() {
     bank.apply(Transaction::Withdrawal {
         client: 2,
         tx: 2000,
-        amount: BigDecimal::from_str("72.6052").unwrap(),
-        disputed: false,
-    });
+        amount: Amount::from_str("72.6052").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
     bank.apply(Transaction::Dispute {
         client: 2,
         referenced_tx: 2000,
-    });
+        amount: None,
+    })
+    .unwrap();
     bank.apply(Transaction::ChargeBack {
         client: 2,
         referenced_tx: 2000,
-    });
+        amount: None,
+    })
+    .unwrap();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("672.62").unwrap(),
-            held: BigDecimal::default(),
-            locked: true,
+            available: Amount::from_str("672.62").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Locked,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
@@ -289,30 +413,51 @@ fn it_handles_a_chargeback_of_a_disputed_withdrawal() {
 fn it_ignores_the_transaction_if_the_account_has_insufficient_funds_on_a_withdrawal() {
     let mut bank = Bank::default();
     apply_test_deposits(&mut bank);
-    bank.apply(Transaction::Withdrawal {
-        client: 2,
-        tx: 2000,
-        amount: BigDecimal::from_str("1000.00").unwrap(),
-        disputed: false,
-    });
+    let rejection = bank
+        .apply(Transaction::Withdrawal {
+            client: 2,
+            tx: 2000,
+            amount: Amount::from_str("1000.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        })
+        .unwrap_err();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("672.62").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("672.62").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
     let mut actual = bank.balances().collect::<Vec<_>>();
     actual.sort_by_key(|v| v.client);
     // Assert
+    assert_eq!(
+        Rejection::InsufficientFunds {
+            client: 2,
+            tx: 2000
+        },
+        rejection
+    );
     for (expected, actual) in expected.iter().zip(actual) {
         assert_eq!(*expected, *actual);
     }
@@ -322,28 +467,48 @@ fn it_ignores_the_transaction_if_the_account_has_insufficient_funds_on_a_withdra
 fn it_ignores_the_transaction_if_the_referenced_transaction_does_not_exist_on_a_dispute() {
     let mut bank = Bank::default();
     apply_test_deposits(&mut bank);
-    bank.apply(Transaction::Dispute {
-        client: 2,
-        referenced_tx: 201,
-    });
+    let rejection = bank
+        .apply(Transaction::Dispute {
+            client: 2,
+            referenced_tx: 201,
+            amount: None,
+        })
+        .unwrap_err();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("672.62").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("672.62").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
     let mut actual = bank.balances().collect::<Vec<_>>();
     actual.sort_by_key(|v| v.client);
     // Assert
+    assert_eq!(
+        Rejection::UnknownTransaction {
+            client: 2,
+            referenced_tx: 201
+        },
+        rejection
+    );
     for (expected, actual) in expected.iter().zip(actual) {
         assert_eq!(*expected, *actual);
     }
@@ -356,29 +521,51 @@ fn it_ignores_the_transaction_if_the_referenced_transaction_is_already_disputed_
     bank.apply(Transaction::Dispute {
         client: 2,
         referenced_tx: 200,
-    });
-    bank.apply(Transaction::Dispute {
-        client: 2,
-        referenced_tx: 200,
-    });
+        amount: None,
+    })
+    .unwrap();
+    let rejection = bank
+        .apply(Transaction::Dispute {
+            client: 2,
+            referenced_tx: 200,
+            amount: None,
+        })
+        .unwrap_err();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("200.0022").unwrap(),
-            held: BigDecimal::from_str("472.6178").unwrap(),
-            locked: false,
+            available: Amount::from_str("200.0022").unwrap(),
+            held: Amount::from_str("472.6178").unwrap(),
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
     let mut actual = bank.balances().collect::<Vec<_>>();
     actual.sort_by_key(|v| v.client);
     // Assert
+    assert_eq!(
+        Rejection::AlreadyDisputed {
+            client: 2,
+            referenced_tx: 200
+        },
+        rejection
+    );
     for (expected, actual) in expected.iter().zip(actual) {
         assert_eq!(*expected, *actual);
     }
@@ -391,29 +578,51 @@ fn it_ignores_the_transaction_if_the_referenced_transaction_does_not_exist_on_a_
     bank.apply(Transaction::Dispute {
         client: 2,
         referenced_tx: 200,
-    });
-    bank.apply(Transaction::Resolve {
-        client: 2,
-        referenced_tx: 201,
-    });
+        amount: None,
+    })
+    .unwrap();
+    let rejection = bank
+        .apply(Transaction::Resolve {
+            client: 2,
+            referenced_tx: 201,
+            amount: None,
+        })
+        .unwrap_err();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("200.0022").unwrap(),
-            held: BigDecimal::from_str("472.6178").unwrap(),
-            locked: false,
+            available: Amount::from_str("200.0022").unwrap(),
+            held: Amount::from_str("472.6178").unwrap(),
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
     let mut actual = bank.balances().collect::<Vec<_>>();
     actual.sort_by_key(|v| v.client);
     // Assert
+    assert_eq!(
+        Rejection::UnknownTransaction {
+            client: 2,
+            referenced_tx: 201
+        },
+        rejection
+    );
     for (expected, actual) in expected.iter().zip(actual) {
         assert_eq!(*expected, *actual);
     }
@@ -426,29 +635,51 @@ fn it_ignores_the_transaction_if_the_referenced_transaction_is_not_under_dispute
     bank.apply(Transaction::Dispute {
         client: 2,
         referenced_tx: 200,
-    });
-    bank.apply(Transaction::Resolve {
-        client: 2,
-        referenced_tx: 2,
-    });
+        amount: None,
+    })
+    .unwrap();
+    let rejection = bank
+        .apply(Transaction::Resolve {
+            client: 2,
+            referenced_tx: 2,
+            amount: None,
+        })
+        .unwrap_err();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("200.0022").unwrap(),
-            held: BigDecimal::from_str("472.6178").unwrap(),
-            locked: false,
+            available: Amount::from_str("200.0022").unwrap(),
+            held: Amount::from_str("472.6178").unwrap(),
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
     let mut actual = bank.balances().collect::<Vec<_>>();
     actual.sort_by_key(|v| v.client);
     // Assert
+    assert_eq!(
+        Rejection::NotDisputed {
+            client: 2,
+            referenced_tx: 2
+        },
+        rejection
+    );
     for (expected, actual) in expected.iter().zip(actual) {
         assert_eq!(*expected, *actual);
     }
@@ -461,29 +692,51 @@ fn it_ignores_the_transaction_if_the_referenced_transaction_does_not_exist_on_a_
     bank.apply(Transaction::Dispute {
         client: 2,
         referenced_tx: 200,
-    });
-    bank.apply(Transaction::ChargeBack {
-        client: 2,
-        referenced_tx: 201,
-    });
+        amount: None,
+    })
+    .unwrap();
+    let rejection = bank
+        .apply(Transaction::ChargeBack {
+            client: 2,
+            referenced_tx: 201,
+            amount: None,
+        })
+        .unwrap_err();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("200.0022").unwrap(),
-            held: BigDecimal::from_str("472.6178").unwrap(),
-            locked: false,
+            available: Amount::from_str("200.0022").unwrap(),
+            held: Amount::from_str("472.6178").unwrap(),
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
     let mut actual = bank.balances().collect::<Vec<_>>();
     actual.sort_by_key(|v| v.client);
     // Assert
+    assert_eq!(
+        Rejection::UnknownTransaction {
+            client: 2,
+            referenced_tx: 201
+        },
+        rejection
+    );
     for (expected, actual) in expected.iter().zip(actual) {
         assert_eq!(*expected, *actual);
     }
@@ -496,57 +749,2971 @@ fn it_ignores_the_transaction_if_the_referenced_transaction_is_not_under_dispute
     bank.apply(Transaction::Dispute {
         client: 2,
         referenced_tx: 200,
-    });
-    bank.apply(Transaction::ChargeBack {
-        client: 2,
-        referenced_tx: 2,
-    });
+        amount: None,
+    })
+    .unwrap();
+    let rejection = bank
+        .apply(Transaction::ChargeBack {
+            client: 2,
+            referenced_tx: 2,
+            amount: None,
+        })
+        .unwrap_err();
     let expected = [
         Account {
             client: 1,
-            available: BigDecimal::from_str("400.2800").unwrap(),
-            held: BigDecimal::default(),
-            locked: false,
+            available: Amount::from_str("400.2800").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
         Account {
             client: 2,
-            available: BigDecimal::from_str("200.0022").unwrap(),
-            held: BigDecimal::from_str("472.6178").unwrap(),
-            locked: false,
+            available: Amount::from_str("200.0022").unwrap(),
+            held: Amount::from_str("472.6178").unwrap(),
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
         },
     ];
     // Act
     let mut actual = bank.balances().collect::<Vec<_>>();
     actual.sort_by_key(|v| v.client);
     // Assert
+    assert_eq!(
+        Rejection::NotDisputed {
+            client: 2,
+            referenced_tx: 2
+        },
+        rejection
+    );
     for (expected, actual) in expected.iter().zip(actual) {
         assert_eq!(*expected, *actual);
     }
 }
 
-fn apply_test_deposits(bank: &mut Bank) {
-    bank.apply(Transaction::Deposit {
+#[test]
+fn it_rejects_the_transaction_if_the_client_has_no_account_on_a_withdrawal() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    // Act
+    let rejection = bank
+        .apply(Transaction::Withdrawal {
+            client: 3,
+            tx: 2000,
+            amount: Amount::from_str("1.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        })
+        .unwrap_err();
+    // Assert
+    assert_eq!(Rejection::UnknownClient { client: 3 }, rejection);
+    assert_eq!(2, bank.balances().count());
+}
+
+#[test]
+fn it_returns_the_applied_effect_of_each_transaction() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    let amount = Amount::from_str("72.6052").unwrap();
+    let disputed = amount.checked_neg().unwrap();
+    // Act
+    let actual = [
+        bank.apply(Transaction::Withdrawal {
+            client: 2,
+            tx: 2000,
+            amount,
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        }),
+        bank.apply(Transaction::Dispute {
+            client: 2,
+            referenced_tx: 2000,
+            amount: None,
+        }),
+        bank.apply(Transaction::Resolve {
+            client: 2,
+            referenced_tx: 2000,
+            amount: None,
+        }),
+        bank.apply(Transaction::Dispute {
+            client: 2,
+            referenced_tx: 2000,
+            amount: None,
+        }),
+        bank.apply(Transaction::ChargeBack {
+            client: 2,
+            referenced_tx: 2000,
+            amount: None,
+        }),
+    ];
+    // Assert
+    let expected = [
+        Ok(AppliedEffect::Debited {
+            client: 2,
+            tx: 2000,
+            amount,
+        }),
+        Ok(AppliedEffect::Held {
+            client: 2,
+            tx: 2000,
+            amount: disputed,
+        }),
+        Ok(AppliedEffect::Released {
+            client: 2,
+            tx: 2000,
+            amount: disputed,
+        }),
+        Ok(AppliedEffect::Held {
+            client: 2,
+            tx: 2000,
+            amount: disputed,
+        }),
+        Ok(AppliedEffect::Reversed {
+            client: 2,
+            tx: 2000,
+            amount: disputed,
+        }),
+    ];
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn it_rejects_all_transactions_on_a_locked_account_by_default() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    lock_test_account(&mut bank);
+    // Act
+    let actual = [
+        bank.apply(Transaction::Deposit {
+            client: 2,
+            tx: 3000,
+            amount: Amount::from_str("10.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        }),
+        bank.apply(Transaction::Withdrawal {
+            client: 2,
+            tx: 3001,
+            amount: Amount::from_str("10.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        }),
+        bank.apply(Transaction::Dispute {
+            client: 2,
+            referenced_tx: 2,
+            amount: None,
+        }),
+    ];
+    // Assert
+    for actual in actual {
+        assert_eq!(Err(Rejection::AccountLocked { client: 2 }), actual);
+    }
+    let mut actual = bank.balances().collect::<Vec<_>>();
+    actual.sort_by_key(|v| v.client);
+    assert_eq!(
+        Account {
+            client: 2,
+            available: Amount::from_str("200.0022").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Locked,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        },
+        *actual[1]
+    );
+}
+
+#[test]
+fn it_allows_only_disputes_and_resolves_on_a_locked_account_with_the_allow_disputes_policy() {
+    // Arrange
+    let mut bank = Bank::default().with_freeze_policy(FreezePolicy::AllowDisputes);
+    apply_test_deposits(&mut bank);
+    lock_test_account(&mut bank);
+    // Act
+    let deposit = bank.apply(Transaction::Deposit {
+        client: 2,
+        tx: 3000,
+        amount: Amount::from_str("10.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    });
+    let dispute = bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 2,
+        amount: None,
+    });
+    let chargeback = bank.apply(Transaction::ChargeBack {
+        client: 2,
+        referenced_tx: 2,
+        amount: None,
+    });
+    let resolve = bank.apply(Transaction::Resolve {
+        client: 2,
+        referenced_tx: 2,
+        amount: None,
+    });
+    // Assert
+    assert_eq!(Err(Rejection::AccountLocked { client: 2 }), deposit);
+    assert!(dispute.is_ok());
+    assert_eq!(Err(Rejection::AccountLocked { client: 2 }), chargeback);
+    assert!(resolve.is_ok());
+    let mut actual = bank.balances().collect::<Vec<_>>();
+    actual.sort_by_key(|v| v.client);
+    assert_eq!(
+        Account {
+            client: 2,
+            available: Amount::from_str("200.0022").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Locked,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        },
+        *actual[1]
+    );
+}
+
+#[test]
+fn it_allows_only_deposits_on_a_locked_account_with_the_allow_deposits_policy() {
+    // Arrange
+    let mut bank = Bank::default().with_freeze_policy(FreezePolicy::AllowDeposits);
+    apply_test_deposits(&mut bank);
+    lock_test_account(&mut bank);
+    // Act
+    let deposit = bank.apply(Transaction::Deposit {
+        client: 2,
+        tx: 3000,
+        amount: Amount::from_str("10.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    });
+    let withdrawal = bank.apply(Transaction::Withdrawal {
+        client: 2,
+        tx: 3001,
+        amount: Amount::from_str("10.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    });
+    let dispute = bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 2,
+        amount: None,
+    });
+    // Assert
+    assert!(deposit.is_ok());
+    assert_eq!(Err(Rejection::AccountLocked { client: 2 }), withdrawal);
+    assert_eq!(Err(Rejection::AccountLocked { client: 2 }), dispute);
+    let mut actual = bank.balances().collect::<Vec<_>>();
+    actual.sort_by_key(|v| v.client);
+    assert_eq!(
+        Account {
+            client: 2,
+            available: Amount::from_str("210.0022").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Locked,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        },
+        *actual[1]
+    );
+}
+
+#[test]
+fn it_unlocks_a_locked_account_with_an_unlock_transaction() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    lock_test_account(&mut bank);
+    // Act
+    let unlock = bank.apply(Transaction::Unlock {
+        client: 2,
+        tx: 3000,
+    });
+    let withdrawal = bank.apply(Transaction::Withdrawal {
+        client: 2,
+        tx: 3001,
+        amount: Amount::from_str("10.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    });
+    let unlock_again = bank.apply(Transaction::Unlock {
+        client: 2,
+        tx: 3002,
+    });
+    // Assert
+    assert_eq!(
+        Ok(AppliedEffect::Unlocked {
+            client: 2,
+            tx: 3000
+        }),
+        unlock
+    );
+    assert!(withdrawal.is_ok());
+    assert_eq!(Err(Rejection::NotLocked { client: 2 }), unlock_again);
+    assert_eq!(AccountStatus::Active, test_account(&bank, 2).status);
+}
+
+#[test]
+fn it_rejects_closing_an_account_with_a_non_zero_balance() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    // Act
+    let actual = bank.apply(Transaction::Close {
         client: 1,
-        tx: 1,
-        amount: BigDecimal::from_str("100.0057").unwrap(),
-        disputed: false,
+        tx: 3000,
     });
+    // Assert
+    assert_eq!(Err(Rejection::NonZeroBalance { client: 1 }), actual);
+    assert_eq!(AccountStatus::Active, test_account(&bank, 1).status);
+}
+
+#[test]
+fn it_rejects_closing_a_locked_account() {
+    // Arrange
+    let mut bank = Bank::default();
     bank.apply(Transaction::Deposit {
-        client: 2,
+        client: 1,
         tx: 1,
-        amount: BigDecimal::from_str("200.0022").unwrap(),
-        disputed: false,
+        amount: Amount::from_str("10.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 1,
+        amount: None,
+    })
+    .unwrap();
+    bank.apply(Transaction::ChargeBack {
+        client: 1,
+        referenced_tx: 1,
+        amount: None,
+    })
+    .unwrap();
+    // Act
+    let close = bank.apply(Transaction::Close { client: 1, tx: 2 });
+    let reopen = bank.apply(Transaction::Reopen { client: 1, tx: 3 });
+    // Assert
+    assert_eq!(Err(Rejection::AccountLocked { client: 1 }), close);
+    assert_eq!(Err(Rejection::NotClosed { client: 1 }), reopen);
+    assert_eq!(AccountStatus::Locked, test_account(&bank, 1).status);
+}
+
+#[test]
+fn it_rejects_all_but_a_reopen_on_a_closed_account() {
+    // Arrange
+    let mut bank = Bank::default().with_freeze_policy(FreezePolicy::AllowDeposits);
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Withdrawal {
+        client: 1,
+        tx: 3000,
+        amount: test_account(&bank, 1).available,
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    // Act
+    let close = bank.apply(Transaction::Close {
+        client: 1,
+        tx: 3001,
     });
-    bank.apply(Transaction::Deposit {
+    let deposit = bank.apply(Transaction::Deposit {
         client: 1,
-        tx: 100,
-        amount: BigDecimal::from_str("300.2743").unwrap(),
-        disputed: false,
+        tx: 3002,
+        amount: Amount::from_str("10.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    });
+    let unlock = bank.apply(Transaction::Unlock {
+        client: 1,
+        tx: 3003,
+    });
+    let reopen = bank.apply(Transaction::Reopen {
+        client: 1,
+        tx: 3004,
+    });
+    let reopen_again = bank.apply(Transaction::Reopen {
+        client: 1,
+        tx: 3005,
     });
+    // Assert
+    assert_eq!(
+        Ok(AppliedEffect::Closed {
+            client: 1,
+            tx: 3001
+        }),
+        close
+    );
+    assert_eq!(Err(Rejection::AccountClosed { client: 1 }), deposit);
+    assert_eq!(Err(Rejection::AccountClosed { client: 1 }), unlock);
+    assert_eq!(
+        Ok(AppliedEffect::Reopened {
+            client: 1,
+            tx: 3004
+        }),
+        reopen
+    );
+    assert_eq!(Err(Rejection::NotClosed { client: 1 }), reopen_again);
+    assert_eq!(AccountStatus::Active, test_account(&bank, 1).status);
+}
+
+#[test]
+fn it_transfers_funds_from_one_client_to_another() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    // Act
+    let actual = bank.apply(Transaction::Transfer {
+        client: 1,
+        to: 2,
+        tx: 3000,
+        amount: Amount::from_str("100.28").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    });
+    // Assert
+    assert_eq!(
+        Ok(AppliedEffect::Transferred {
+            client: 1,
+            to: 2,
+            tx: 3000,
+            amount: Amount::from_str("100.28").unwrap(),
+        }),
+        actual
+    );
+    assert_eq!(
+        Amount::from_str("300.0000").unwrap(),
+        test_account(&bank, 1).available
+    );
+    assert_eq!(
+        Amount::from_str("772.9000").unwrap(),
+        test_account(&bank, 2).available
+    );
+}
+
+#[test]
+fn it_rejects_a_transfer_without_funds_to_itself_or_to_a_locked_account() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    lock_test_account(&mut bank);
     bank.apply(Transaction::Deposit {
+        client: 4,
+        tx: 2999,
+        amount: Amount::from_str("1.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    let transfer = |to: u16, tx: u32, amount: &str| Transaction::Transfer {
+        client: 1,
+        to,
+        tx,
+        amount: Amount::from_str(amount).unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    };
+    let before = bank.snapshot();
+    // Act
+    let actual = [
+        bank.apply(transfer(3, 3000, "1.00")),
+        bank.apply(transfer(1, 3001, "1.00")),
+        bank.apply(transfer(2, 3002, "1.00")),
+        bank.apply(transfer(4, 3003, "500.00")),
+    ];
+    // Assert
+    assert_eq!(
+        [
+            Err(Rejection::UnknownClient { client: 3 }),
+            Err(Rejection::SelfTransfer {
+                client: 1,
+                tx: 3001
+            }),
+            Err(Rejection::AccountLocked { client: 2 }),
+            Err(Rejection::InsufficientFunds {
+                client: 1,
+                tx: 3003
+            }),
+        ],
+        actual
+    );
+    assert_eq!(before, bank.snapshot());
+}
+
+#[test]
+fn it_rejects_disputing_a_transfer_to_a_closed_account() {
+    // Arrange
+    let mut bank = Bank::default();
+    let deposit = |client: u16, tx: u32, amount: &str| Transaction::Deposit {
+        client,
+        tx,
+        amount: Amount::from_str(amount).unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    };
+    bank.apply(deposit(1, 1, "100.00")).unwrap();
+    bank.apply(deposit(2, 2, "1.00")).unwrap();
+    bank.apply(Transaction::Transfer {
+        client: 1,
+        to: 2,
+        tx: 3,
+        amount: Amount::from_str("30.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    bank.apply(Transaction::Withdrawal {
         client: 2,
-        tx: 200,
-        amount: BigDecimal::from_str("472.6178").unwrap(),
-        disputed: false,
+        tx: 4,
+        amount: Amount::from_str("31.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    bank.apply(Transaction::Close { client: 2, tx: 5 }).unwrap();
+    let before = bank.snapshot();
+    // Act
+    let dispute = bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 3,
+        amount: None,
+    });
+    let chargeback = bank.apply(Transaction::ChargeBack {
+        client: 1,
+        referenced_tx: 3,
+        amount: None,
+    });
+    // Assert
+    assert_eq!(Err(Rejection::AccountClosed { client: 2 }), dispute);
+    assert_eq!(Err(Rejection::AccountClosed { client: 2 }), chargeback);
+    assert_eq!(before, bank.snapshot());
+    assert!(bank.verify().is_empty());
+}
+
+#[test]
+fn it_returns_a_charged_back_transfer_to_the_sender() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Transfer {
+        client: 1,
+        to: 2,
+        tx: 3000,
+        amount: Amount::from_str("50.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    // Act
+    let dispute = bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 3000,
+        amount: None,
     });
+    let chargeback = bank.apply(Transaction::ChargeBack {
+        client: 1,
+        referenced_tx: 3000,
+        amount: None,
+    });
+    // Assert
+    let amount = Amount::from_str("50.00").unwrap();
+    assert_eq!(
+        Ok(AppliedEffect::Held {
+            client: 2,
+            tx: 3000,
+            amount
+        }),
+        dispute
+    );
+    assert_eq!(
+        Ok(AppliedEffect::Returned {
+            client: 2,
+            to: 1,
+            tx: 3000,
+            amount
+        }),
+        chargeback
+    );
+    assert_eq!(
+        Amount::from_str("400.2800").unwrap(),
+        test_account(&bank, 1).available
+    );
+    let destination = test_account(&bank, 2);
+    assert_eq!(Amount::from_str("672.6200").unwrap(), destination.available);
+    assert_eq!(Amount::ZERO, destination.held);
+    assert_eq!(AccountStatus::Locked, destination.status);
+    assert!(matches!(
+        stored_test_transaction(&bank, 3000),
+        Transaction::Transfer {
+            state: DisputeState::ChargedBack,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn it_allows_withdrawals_to_overdraw_an_account_up_to_its_credit_limit() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    let withdrawal = |tx: u32, amount: &str| Transaction::Withdrawal {
+        client: 1,
+        tx,
+        amount: Amount::from_str(amount).unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    };
+    // Act
+    let limit = bank.apply(Transaction::CreditLimit {
+        client: 1,
+        tx: 3000,
+        amount: Amount::from_str("100").unwrap(),
+    });
+    let within = bank.apply(withdrawal(3001, "450.00"));
+    let beyond = bank.apply(withdrawal(3002, "60.00"));
+    // Assert
+    assert_eq!(
+        Ok(AppliedEffect::CreditLimitSet {
+            client: 1,
+            tx: 3000,
+            amount: Amount::from_str("100").unwrap(),
+        }),
+        limit
+    );
+    assert!(within.is_ok());
+    assert_eq!(
+        Err(Rejection::InsufficientFunds {
+            client: 1,
+            tx: 3002
+        }),
+        beyond
+    );
+    let acct = test_account(&bank, 1);
+    assert_eq!(Amount::from_str("-49.72").unwrap(), acct.available);
+    assert_eq!(
+        Amount::from_str("50.28").unwrap(),
+        acct.remaining_credit().unwrap()
+    );
+}
+
+#[test]
+fn it_rejects_a_negative_credit_limit() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    // Act
+    let actual = bank.apply(Transaction::CreditLimit {
+        client: 1,
+        tx: 3000,
+        amount: Amount::from_str("-100").unwrap(),
+    });
+    // Assert
+    assert_eq!(
+        Err(Rejection::NegativeCreditLimit {
+            client: 1,
+            tx: 3000
+        }),
+        actual
+    );
+    assert_eq!(Amount::ZERO, test_account(&bank, 1).credit_limit);
+}
+
+#[test]
+fn it_rounds_fees_to_the_input_scale_of_the_precision_policy() {
+    // Arrange
+    let mut bank = Bank::default()
+        .with_precision_policy(PrecisionPolicy {
+            max_input_scale: 2,
+            ..PrecisionPolicy::default()
+        })
+        .with_fee_schedule(FeeSchedule {
+            withdrawal_flat: Amount::from_str("0.50").unwrap(),
+            withdrawal_percent: Amount::from_str("1").unwrap(),
+            maintenance: Amount::from_str("0.255").unwrap(),
+            ..FeeSchedule::default()
+        });
+    bank.apply(Transaction::Deposit {
+        client: 1,
+        tx: 1,
+        amount: Amount::from_str("100.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    // Act
+    let withdrawal = bank.apply_with_fees(Transaction::Withdrawal {
+        client: 1,
+        tx: 3000,
+        amount: Amount::from_str("10.01").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    });
+    let maintenance = bank.apply(Transaction::Maintenance {
+        client: 1,
+        tx: 3001,
+    });
+    // Assert
+    assert_eq!(
+        Some(&AppliedEffect::FeeCharged {
+            client: 1,
+            tx: 3000,
+            amount: Amount::from_str("0.60").unwrap(),
+        }),
+        withdrawal.unwrap().get(1)
+    );
+    assert_eq!(
+        Ok(AppliedEffect::FeeCharged {
+            client: 1,
+            tx: 3001,
+            amount: Amount::from_str("0.26").unwrap(),
+        }),
+        maintenance
+    );
+}
+
+#[test]
+fn it_charges_a_flat_and_percentage_withdrawal_fee_as_a_separate_effect() {
+    // Arrange
+    let mut bank = Bank::default().with_fee_schedule(FeeSchedule {
+        withdrawal_flat: Amount::from_str("0.50").unwrap(),
+        withdrawal_percent: Amount::from_str("1").unwrap(),
+        ..FeeSchedule::default()
+    });
+    apply_test_deposits(&mut bank);
+    let withdrawal = |tx: u32, amount: &str| Transaction::Withdrawal {
+        client: 1,
+        tx,
+        amount: Amount::from_str(amount).unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    };
+    // Act
+    let charged = bank.apply_with_fees(withdrawal(3000, "100.00"));
+    let short_of_fee = bank.apply_with_fees(withdrawal(3001, "298.00"));
+    // Assert
+    assert_eq!(
+        Ok(vec![
+            AppliedEffect::Debited {
+                client: 1,
+                tx: 3000,
+                amount: Amount::from_str("100.00").unwrap(),
+            },
+            AppliedEffect::FeeCharged {
+                client: 1,
+                tx: 3000,
+                amount: Amount::from_str("1.50").unwrap(),
+            },
+        ]),
+        charged
+    );
+    assert_eq!(
+        Err(Rejection::InsufficientFunds {
+            client: 1,
+            tx: 3001
+        }),
+        short_of_fee
+    );
+    assert_eq!(
+        Amount::from_str("298.78").unwrap(),
+        test_account(&bank, 1).available
+    );
+}
+
+#[test]
+fn it_charges_a_penalty_for_a_deposit_chargeback() {
+    // Arrange
+    let mut bank = Bank::default().with_fee_schedule(FeeSchedule {
+        chargeback: Amount::from_str("15").unwrap(),
+        ..FeeSchedule::default()
+    });
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 1,
+        amount: None,
+    })
+    .unwrap();
+    // Act
+    let actual = bank.apply_with_fees(Transaction::ChargeBack {
+        client: 1,
+        referenced_tx: 1,
+        amount: None,
+    });
+    // Assert
+    assert_eq!(
+        Ok(AppliedEffect::FeeCharged {
+            client: 1,
+            tx: 1,
+            amount: Amount::from_str("15").unwrap(),
+        }),
+        actual.map(|effects| effects[1].clone())
+    );
+    assert_eq!(
+        Amount::from_str("285.2743").unwrap(),
+        test_account(&bank, 1).available
+    );
+}
+
+#[test]
+fn it_refunds_the_fee_of_a_withdrawal_charged_back_in_the_clients_favor() {
+    // Arrange
+    let mut bank = Bank::default().with_fee_schedule(FeeSchedule {
+        withdrawal_flat: Amount::from_str("2").unwrap(),
+        chargeback: Amount::from_str("15").unwrap(),
+        ..FeeSchedule::default()
+    });
+    apply_test_deposits(&mut bank);
+    apply_test_withdrawal(&mut bank);
+    bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 2000,
+        amount: None,
+    })
+    .unwrap();
+    // Act
+    let actual = bank.apply_with_fees(Transaction::ChargeBack {
+        client: 2,
+        referenced_tx: 2000,
+        amount: None,
+    });
+    // Assert
+    assert_eq!(
+        Ok(vec![
+            AppliedEffect::Reversed {
+                client: 2,
+                tx: 2000,
+                amount: Amount::from_str("-72.6052").unwrap(),
+            },
+            AppliedEffect::FeeRefunded {
+                client: 2,
+                tx: 2000,
+                amount: Amount::from_str("2").unwrap(),
+            },
+        ]),
+        actual
+    );
+    assert_eq!(
+        Amount::from_str("672.6200").unwrap(),
+        test_account(&bank, 2).available
+    );
+    assert_eq!(
+        vec![Fee {
+            client: 2,
+            tx: 2000,
+            amount: Amount::ZERO,
+        }],
+        bank.snapshot().fees
+    );
+}
+
+#[test]
+fn it_charges_a_maintenance_fee_only_when_one_is_configured() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    let mut charging = Bank::default().with_fee_schedule(FeeSchedule {
+        maintenance: Amount::from_str("2.5").unwrap(),
+        ..FeeSchedule::default()
+    });
+    apply_test_deposits(&mut charging);
+    lock_test_account(&mut charging);
+    let maintenance = Transaction::Maintenance {
+        client: 2,
+        tx: 3000,
+    };
+    // Act
+    let free = bank.apply(maintenance.clone());
+    let charged = charging.apply(maintenance.clone());
+    let duplicate = charging.apply(maintenance);
+    // Assert
+    assert_eq!(
+        Err(Rejection::NoFee {
+            client: 2,
+            tx: 3000
+        }),
+        free
+    );
+    assert_eq!(
+        Ok(AppliedEffect::FeeCharged {
+            client: 2,
+            tx: 3000,
+            amount: Amount::from_str("2.5").unwrap(),
+        }),
+        charged
+    );
+    assert_eq!(
+        Err(Rejection::DuplicateTransaction {
+            client: 2,
+            tx: 3000
+        }),
+        duplicate
+    );
+    assert_eq!(
+        Amount::from_str("197.5022").unwrap(),
+        test_account(&charging, 2).available
+    );
+}
+
+#[test]
+fn it_reserves_authorized_funds_until_they_are_captured_for_a_different_amount() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Authorize {
+        client: 2,
+        tx: 3000,
+        amount: Amount::from_str("100").unwrap(),
+    })
+    .unwrap();
+    let authorized = test_account(&bank, 2);
+    // Act
+    let excessive = bank.apply(Transaction::Capture {
+        client: 2,
+        tx: 3000,
+        amount: Some(Amount::from_str("772.63").unwrap()),
+    });
+    let actual = bank.apply(Transaction::Capture {
+        client: 2,
+        tx: 3000,
+        amount: Some(Amount::from_str("120").unwrap()),
+    });
+    let repeated = bank.apply(Transaction::Capture {
+        client: 2,
+        tx: 3000,
+        amount: None,
+    });
+    // Assert
+    assert_eq!(Amount::from_str("572.62").unwrap(), authorized.available);
+    assert_eq!(Amount::from_str("100").unwrap(), authorized.authorized);
+    assert_eq!(
+        Amount::from_str("672.62").unwrap(),
+        authorized.total().unwrap()
+    );
+    assert_eq!(
+        Err(Rejection::InsufficientFunds {
+            client: 2,
+            tx: 3000
+        }),
+        excessive
+    );
+    assert_eq!(
+        Ok(AppliedEffect::Captured {
+            client: 2,
+            tx: 3000,
+            amount: Amount::from_str("120").unwrap(),
+        }),
+        actual
+    );
+    assert_eq!(
+        Err(Rejection::UnknownAuthorization {
+            client: 2,
+            tx: 3000
+        }),
+        repeated
+    );
+    let captured = test_account(&bank, 2);
+    assert_eq!(Amount::from_str("552.62").unwrap(), captured.available);
+    assert_eq!(Amount::ZERO, captured.authorized);
+}
+
+#[test]
+fn it_releases_authorized_funds_on_a_void() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Authorize {
+        client: 2,
+        tx: 3000,
+        amount: Amount::from_str("100").unwrap(),
+    })
+    .unwrap();
+    // Act
+    let mismatch = bank.apply(Transaction::Void {
+        client: 1,
+        tx: 3000,
+    });
+    let actual = bank.apply(Transaction::Void {
+        client: 2,
+        tx: 3000,
+    });
+    // Assert
+    assert_eq!(
+        Err(Rejection::UnknownAuthorization {
+            client: 1,
+            tx: 3000
+        }),
+        mismatch
+    );
+    assert_eq!(
+        Ok(AppliedEffect::Voided {
+            client: 2,
+            tx: 3000,
+            amount: Amount::from_str("100").unwrap(),
+        }),
+        actual
+    );
+    let acct = test_account(&bank, 2);
+    assert_eq!(Amount::from_str("672.62").unwrap(), acct.available);
+    assert_eq!(Amount::ZERO, acct.authorized);
+    assert!(bank.snapshot().authorizations.is_empty());
+}
+
+#[test]
+fn it_expires_authorizations_left_uncaptured_after_the_authorization_expiry() {
+    // Arrange
+    let mut bank = Bank::default().with_authorization_expiry(Some(2));
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Authorize {
+        client: 2,
+        tx: 3000,
+        amount: Amount::from_str("100").unwrap(),
+    })
+    .unwrap();
+    apply_test_withdrawal(&mut bank);
+    // Act
+    let pending = bank.expire_authorizations();
+    withdraw_test_deposit(&mut bank);
+    let actual = bank.expire_authorizations();
+    // Assert
+    assert!(pending.is_empty());
+    assert_eq!(
+        vec![AppliedEffect::Expired {
+            client: 2,
+            tx: 3000,
+            amount: Amount::from_str("100").unwrap(),
+        }],
+        actual
+    );
+    assert_eq!(
+        Amount::from_str("600.0148").unwrap(),
+        test_account(&bank, 2).available
+    );
+    assert!(bank.expire_authorizations().is_empty());
+}
+
+#[test]
+fn it_does_not_expire_a_restored_authorization_stamped_after_the_sequence() {
+    // Arrange
+    let mut bank = Bank::restore(Snapshot {
+        authorizations: vec![Authorization {
+            client: 1,
+            tx: 1,
+            amount: Amount::from_str("10").unwrap(),
+            authorized_at: 5,
+        }],
+        sequence: 2,
+        ..Snapshot::default()
+    })
+    .unwrap()
+    .with_authorization_expiry(Some(1));
+    // Act
+    let actual = bank.expire_authorizations();
+    // Assert
+    assert!(actual.is_empty());
+}
+
+#[test]
+fn it_keeps_the_tx_of_a_voided_or_expired_authorization_in_use() {
+    // Arrange
+    let mut bank = Bank::default().with_authorization_expiry(Some(1));
+    apply_test_deposits(&mut bank);
+    for tx in [3000, 3001] {
+        bank.apply(Transaction::Authorize {
+            client: 2,
+            tx,
+            amount: Amount::from_str("10").unwrap(),
+        })
+        .unwrap();
+    }
+    bank.apply(Transaction::Void {
+        client: 2,
+        tx: 3000,
+    })
+    .unwrap();
+    bank.expire_authorizations();
+    let mut restored = Bank::restore(bank.snapshot()).unwrap();
+    let deposit = |tx: u32| Transaction::Deposit {
+        client: 2,
+        tx,
+        amount: Amount::from_str("5").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    };
+    // Act
+    let actual = [
+        bank.apply(deposit(3000)),
+        bank.apply(deposit(3001)),
+        restored.apply(deposit(3000)),
+        restored.apply(deposit(3001)),
+    ];
+    // Assert
+    assert_eq!(
+        [3000, 3001, 3000, 3001].map(|tx| Err(Rejection::DuplicateTransaction { client: 2, tx })),
+        actual
+    );
+    assert!(bank.snapshot().authorizations.is_empty());
+    assert_eq!(
+        vec![
+            ClosedAuthorization {
+                client: 2,
+                tx: 3000
+            },
+            ClosedAuthorization {
+                client: 2,
+                tx: 3001
+            },
+        ],
+        bank.snapshot().closed_authorizations
+    );
+}
+
+#[test]
+fn it_allows_a_captured_authorization_to_be_disputed() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Authorize {
+        client: 2,
+        tx: 3000,
+        amount: Amount::from_str("100").unwrap(),
+    })
+    .unwrap();
+    bank.apply(Transaction::Capture {
+        client: 2,
+        tx: 3000,
+        amount: None,
+    })
+    .unwrap();
+    // Act
+    let actual = bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 3000,
+        amount: None,
+    });
+    // Assert
+    assert_eq!(
+        Ok(AppliedEffect::Held {
+            client: 2,
+            tx: 3000,
+            amount: Amount::from_str("-100").unwrap(),
+        }),
+        actual
+    );
+    assert_eq!(
+        Transaction::Withdrawal {
+            client: 2,
+            tx: 3000,
+            amount: Amount::from_str("100").unwrap(),
+            state: DisputeState::Disputed,
+            disputed: Amount::from_str("100").unwrap(),
+            charged_back: Amount::ZERO,
+        },
+        stored_test_transaction(&bank, 3000)
+    );
+}
+
+#[test]
+fn it_pays_and_charges_interest_by_rate_tier_between_accruals() {
+    // Arrange
+    let rate = |from, rate| RateTier {
+        from: Amount::from_str(from).unwrap(),
+        rate: Amount::from_str(rate).unwrap(),
+    };
+    let mut bank = Bank::default().with_interest_schedule(InterestSchedule {
+        day_count: DayCount::Actual365,
+        credit: vec![rate("0", "1"), rate("300", "2")],
+        debit: vec![rate("0", "18")],
+    });
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::CreditLimit {
+        client: 2,
+        tx: 3000,
+        amount: Amount::from_str("100").unwrap(),
+    })
+    .unwrap();
+    bank.apply(Transaction::Withdrawal {
+        client: 2,
+        tx: 3001,
+        amount: Amount::from_str("772.6200").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    let start = Date::from_str("2024-01-01").unwrap();
+    let end = Date::from_str("2024-01-31").unwrap();
+    // Act
+    let first = bank.apply(Transaction::Accrue {
+        client: 1,
+        tx: 3002,
+        as_of: start,
+    });
+    bank.apply(Transaction::Accrue {
+        client: 2,
+        tx: 3003,
+        as_of: start,
+    })
+    .unwrap();
+    let actual = bank.accrue(end);
+    // Assert
+    assert_eq!(
+        Ok(AppliedEffect::InterestPosted {
+            client: 1,
+            tx: 3002,
+            amount: Amount::ZERO,
+            as_of: start,
+        }),
+        first
+    );
+    assert_eq!(
+        vec![
+            AppliedEffect::InterestPosted {
+                client: 1,
+                tx: 0,
+                amount: Amount::from_str("0.6580").unwrap(),
+                as_of: end,
+            },
+            AppliedEffect::InterestPosted {
+                client: 2,
+                tx: 0,
+                amount: Amount::from_str("-1.4795").unwrap(),
+                as_of: end,
+            },
+        ],
+        actual
+    );
+    assert_eq!(
+        Amount::from_str("400.9380").unwrap(),
+        test_account(&bank, 1).available
+    );
+    assert_eq!(
+        Amount::from_str("-101.4795").unwrap(),
+        test_account(&bank, 2).available
+    );
+}
+
+#[test]
+fn it_rounds_interest_to_the_input_scale_of_the_precision_policy() {
+    // Arrange
+    let mut bank = Bank::default()
+        .with_precision_policy(PrecisionPolicy {
+            max_input_scale: 2,
+            ..PrecisionPolicy::default()
+        })
+        .with_interest_schedule(InterestSchedule {
+            day_count: DayCount::Actual365,
+            credit: vec![RateTier {
+                from: Amount::ZERO,
+                rate: Amount::from_str("12").unwrap(),
+            }],
+            debit: Vec::new(),
+        });
+    bank.apply(Transaction::Deposit {
+        client: 1,
+        tx: 1,
+        amount: Amount::from_str("100.00").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    bank.accrue(Date::from_str("2024-01-01").unwrap());
+    // Act
+    let actual = bank.accrue(Date::from_str("2024-01-31").unwrap());
+    // Assert
+    assert_eq!(
+        vec![AppliedEffect::InterestPosted {
+            client: 1,
+            tx: 0,
+            amount: Amount::from_str("0.99").unwrap(),
+            as_of: Date::from_str("2024-01-31").unwrap(),
+        }],
+        actual
+    );
+}
+
+#[test]
+fn it_rejects_an_accrual_that_is_not_after_the_previous_one() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    let accrue = |tx, as_of| Transaction::Accrue {
+        client: 1,
+        tx,
+        as_of: Date::from_str(as_of).unwrap(),
+    };
+    bank.apply(accrue(3000, "2024-01-31")).unwrap();
+    // Act
+    let same = bank.apply(accrue(3001, "2024-01-31"));
+    let earlier = bank.apply(accrue(3002, "2023-12-31"));
+    // Assert
+    assert_eq!(
+        Err(Rejection::StaleAccrual {
+            client: 1,
+            tx: 3001
+        }),
+        same
+    );
+    assert_eq!(
+        Err(Rejection::StaleAccrual {
+            client: 1,
+            tx: 3002
+        }),
+        earlier
+    );
+}
+
+#[test]
+fn it_counts_accrued_days_by_the_day_count_convention() {
+    // Arrange
+    let from = Date::from_str("2024-01-31").unwrap();
+    let to = Date::from_str("2024-03-01").unwrap();
+    // Act
+    let actual = [
+        DayCount::Actual360,
+        DayCount::Actual365,
+        DayCount::Thirty360,
+    ]
+    .map(|day_count| day_count.days(from, to));
+    // Assert
+    assert_eq!([(30, 360), (30, 365), (31, 360)], actual);
+}
+
+#[test]
+fn it_rejects_a_deposit_reusing_a_transaction_id_of_the_same_client() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    // Act
+    let rejection = bank
+        .apply(Transaction::Deposit {
+            client: 2,
+            tx: 200,
+            amount: Amount::from_str("472.6178").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        })
+        .unwrap_err();
+    let mut actual = bank.balances().collect::<Vec<_>>();
+    actual.sort_by_key(|v| v.client);
+    // Assert
+    assert_eq!(
+        Rejection::DuplicateTransaction { client: 2, tx: 200 },
+        rejection
+    );
+    assert_eq!(Amount::from_str("672.6200").unwrap(), actual[1].available);
+}
+
+#[test]
+fn it_rejects_a_deposit_reusing_a_transaction_id_of_another_client() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    // Act
+    let rejection = bank
+        .apply(Transaction::Deposit {
+            client: 3,
+            tx: 100,
+            amount: Amount::from_str("1.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        })
+        .unwrap_err();
+    // Assert
+    assert_eq!(
+        Rejection::DuplicateTransaction { client: 3, tx: 100 },
+        rejection
+    );
+    assert_eq!(2, bank.balances().count());
+}
+
+#[test]
+fn it_rejects_a_withdrawal_reusing_the_transaction_id_of_a_disputed_deposit() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 200,
+        amount: None,
+    })
+    .unwrap();
+    // Act
+    let rejection = bank
+        .apply(Transaction::Withdrawal {
+            client: 2,
+            tx: 200,
+            amount: Amount::from_str("100.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        })
+        .unwrap_err();
+    let resolve = bank.apply(Transaction::Resolve {
+        client: 2,
+        referenced_tx: 200,
+        amount: None,
+    });
+    let mut actual = bank.balances().collect::<Vec<_>>();
+    actual.sort_by_key(|v| v.client);
+    // Assert
+    assert_eq!(
+        Rejection::DuplicateTransaction { client: 2, tx: 200 },
+        rejection
+    );
+    assert!(resolve.is_ok());
+    assert_eq!(
+        Account {
+            client: 2,
+            available: Amount::from_str("672.6200").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        },
+        *actual[1]
+    );
+}
+
+#[test]
+fn it_rejects_a_dispute_of_a_transaction_of_another_client_as_a_client_mismatch() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    // Act
+    let rejection = bank
+        .apply(Transaction::Dispute {
+            client: 2,
+            referenced_tx: 100,
+            amount: None,
+        })
+        .unwrap_err();
+    // Assert
+    assert_eq!(
+        Rejection::ClientMismatch {
+            client: 2,
+            referenced_tx: 100,
+            owner: 1,
+            suspected_fraud: false,
+        },
+        rejection
+    );
+    assert_eq!("client_mismatch", rejection.code());
+}
+
+#[test]
+fn it_flags_client_mismatches_on_resolves_and_chargebacks_as_suspected_fraud_when_asked_to() {
+    // Arrange
+    let mut bank = Bank::default().with_client_mismatch_flagging(true);
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    })
+    .unwrap();
+    // Act
+    let resolve = bank
+        .apply(Transaction::Resolve {
+            client: 2,
+            referenced_tx: 100,
+            amount: None,
+        })
+        .unwrap_err();
+    let chargeback = bank
+        .apply(Transaction::ChargeBack {
+            client: 2,
+            referenced_tx: 100,
+            amount: None,
+        })
+        .unwrap_err();
+    let unknown = bank
+        .apply(Transaction::ChargeBack {
+            client: 2,
+            referenced_tx: 101,
+            amount: None,
+        })
+        .unwrap_err();
+    // Assert
+    for rejection in [resolve, chargeback] {
+        assert_eq!(
+            Rejection::ClientMismatch {
+                client: 2,
+                referenced_tx: 100,
+                owner: 1,
+                suspected_fraud: true,
+            },
+            rejection
+        );
+        assert_eq!("suspected_fraud", rejection.code());
+    }
+    assert_eq!(
+        Rejection::UnknownTransaction {
+            client: 2,
+            referenced_tx: 101
+        },
+        unknown
+    );
+}
+
+#[test]
+fn it_rebuilds_identical_state_by_replaying_the_journal_of_applied_effects() {
+    // Arrange
+    let mut bank = Bank::default();
+    let mut journal = Vec::new();
+    let transactions = [
+        Transaction::Deposit {
+            client: 1,
+            tx: 1,
+            amount: Amount::from_str("100.0057").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        },
+        Transaction::Deposit {
+            client: 2,
+            tx: 2,
+            amount: Amount::from_str("200.0022").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        },
+        Transaction::Withdrawal {
+            client: 1,
+            tx: 3,
+            amount: Amount::from_str("50.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        },
+        Transaction::Withdrawal {
+            client: 1,
+            tx: 4,
+            amount: Amount::from_str("5000.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        },
+        Transaction::Dispute {
+            client: 1,
+            referenced_tx: 3,
+            amount: None,
+        },
+        Transaction::Dispute {
+            client: 2,
+            referenced_tx: 2,
+            amount: None,
+        },
+        Transaction::ChargeBack {
+            client: 2,
+            referenced_tx: 2,
+            amount: None,
+        },
+    ];
+    for transaction in transactions {
+        if let Ok(effect) = bank.apply(transaction) {
+            journal.push(effect);
+        }
+    }
+    // Act
+    let mut replayed = Bank::replay(journal).unwrap();
+    // Assert
+    assert_eq!(bank, replayed);
+    assert!(replayed
+        .apply(Transaction::Resolve {
+            client: 1,
+            referenced_tx: 3,
+            amount: None,
+        })
+        .is_ok());
+    assert_eq!(
+        Err(Rejection::AccountLocked { client: 2 }),
+        replayed.apply(Transaction::Deposit {
+            client: 2,
+            tx: 5,
+            amount: Amount::from_str("1.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        })
+    );
+}
+
+#[test]
+fn it_counts_a_fee_after_an_accrual_under_the_same_tx_as_its_own_row_on_replay() {
+    // Arrange
+    let mut bank = Bank::default().with_fee_schedule(FeeSchedule {
+        withdrawal_flat: Amount::from_str("0.10").unwrap(),
+        maintenance: Amount::from_str("1.00").unwrap(),
+        ..FeeSchedule::default()
+    });
+    let transactions = [
+        Transaction::Deposit {
+            client: 1,
+            tx: 1,
+            amount: Amount::from_str("100.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        },
+        Transaction::Accrue {
+            client: 1,
+            tx: 5,
+            as_of: Date::from_str("2024-01-01").unwrap(),
+        },
+        Transaction::Maintenance { client: 1, tx: 5 },
+        Transaction::Unlock { client: 1, tx: 6 },
+        Transaction::Withdrawal {
+            client: 1,
+            tx: 6,
+            amount: Amount::from_str("1.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        },
+        Transaction::Withdrawal {
+            client: 1,
+            tx: 7,
+            amount: Amount::from_str("1.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        },
+    ];
+    let mut journal = Vec::new();
+    for transaction in transactions {
+        if let Ok(effects) = bank.apply_with_fees(transaction) {
+            journal.extend(effects);
+        }
+    }
+    // Act
+    let replayed = Bank::replay(journal).unwrap();
+    // Assert
+    assert_eq!(bank.sequence, replayed.sequence);
+}
+
+#[test]
+fn it_fails_to_replay_a_journal_that_overflows_the_ledger() {
+    // Arrange
+    let credited = |client, tx| AppliedEffect::Credited {
+        client,
+        tx,
+        amount: Amount::from_str("900000000000000").unwrap(),
+    };
+    // Act
+    let replayed = Bank::replay([credited(1, 1), credited(2, 2)]);
+    // Assert
+    assert_eq!(Some(AmountError::Overflow), replayed.err());
+}
+
+#[test]
+fn it_fails_to_restore_a_snapshot_that_overflows_the_ledger() {
+    // Arrange
+    let acct = Account {
+        client: 1,
+        available: Amount::from_str("900000000000000").unwrap(),
+        held: Amount::ZERO,
+        status: AccountStatus::Active,
+        pending_credit: Amount::ZERO,
+        flagged: false,
+        receivable: Amount::ZERO,
+        credit_limit: Amount::ZERO,
+        authorized: Amount::ZERO,
+    };
+    // Act
+    let restored = Bank::restore(Snapshot {
+        accounts: vec![acct.clone(), acct],
+        ..Snapshot::default()
+    });
+    // Assert
+    assert_eq!(Some(AmountError::Overflow), restored.err());
+}
+
+#[test]
+fn it_restores_identical_state_from_a_snapshot() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 200,
+        amount: None,
+    })
+    .unwrap();
+    // Act
+    let mut restored = Bank::restore(bank.snapshot()).unwrap();
+    // Assert
+    assert_eq!(bank, restored);
+    assert!(restored
+        .apply(Transaction::ChargeBack {
+            client: 2,
+            referenced_tx: 200,
+            amount: None,
+        })
+        .is_ok());
+}
+
+#[test]
+fn it_posts_balanced_ledger_entries_that_the_account_balances_are_derived_from() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    apply_test_withdrawal(&mut bank);
+    // Act
+    lock_test_account(&mut bank);
+    // Assert
+    assert_eq!(
+        vec![
+            LedgerBalance {
+                account: LedgerAccount::Available(1),
+                balance: Amount::from_str("-400.28").unwrap(),
+            },
+            LedgerBalance {
+                account: LedgerAccount::Available(2),
+                balance: Amount::from_str("-127.397").unwrap(),
+            },
+            LedgerBalance {
+                account: LedgerAccount::Cash,
+                balance: Amount::from_str("527.677").unwrap(),
+            },
+        ],
+        bank.ledger().balances()
+    );
+    assert_eq!(
+        TrialBalance {
+            debits: Amount::from_str("527.677").unwrap(),
+            credits: Amount::from_str("527.677").unwrap(),
+        },
+        bank.ledger().trial_balance().unwrap()
+    );
+    assert!(bank.ledger().is_balanced());
+    let acct = test_account(&bank, 2);
+    assert_eq!(Amount::from_str("127.397").unwrap(), acct.available);
+    assert_eq!(Amount::ZERO, acct.held);
+}
+
+#[test]
+fn it_restores_the_bank_ledger_balances_from_a_snapshot() {
+    // Arrange
+    let mut bank = Bank::default().with_fee_schedule(FeeSchedule {
+        chargeback: Amount::from_str("15").unwrap(),
+        ..FeeSchedule::default()
+    });
+    apply_test_deposits(&mut bank);
+    lock_test_account(&mut bank);
+    let snapshot = bank.snapshot();
+    // Act
+    let restored = Bank::restore(snapshot.clone()).unwrap();
+    // Assert
+    assert_eq!(
+        vec![
+            LedgerBalance {
+                account: LedgerAccount::Cash,
+                balance: Amount::from_str("600.2822").unwrap(),
+            },
+            LedgerBalance {
+                account: LedgerAccount::FeeIncome,
+                balance: Amount::from_str("-15").unwrap(),
+            },
+        ],
+        snapshot.ledger
+    );
+    assert_eq!(bank.ledger(), restored.ledger());
+    assert!(restored.ledger().is_balanced());
+}
+
+#[test]
+fn it_records_the_history_of_each_account_with_running_balances() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Transfer {
+        client: 1,
+        to: 2,
+        tx: 3000,
+        amount: Amount::from_str("100").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    // Act
+    lock_test_account(&mut bank);
+    // Assert
+    let amount = |amount| Amount::from_str(amount).unwrap();
+    let actual = bank
+        .history(2)
+        .iter()
+        .map(|entry| (entry.effect.clone(), entry.available, entry.held))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (
+                AppliedEffect::Credited {
+                    client: 2,
+                    tx: 2,
+                    amount: amount("200.0022"),
+                },
+                amount("200.0022"),
+                Amount::ZERO,
+            ),
+            (
+                AppliedEffect::Credited {
+                    client: 2,
+                    tx: 200,
+                    amount: amount("472.6178"),
+                },
+                amount("672.62"),
+                Amount::ZERO,
+            ),
+            (
+                AppliedEffect::Transferred {
+                    client: 1,
+                    to: 2,
+                    tx: 3000,
+                    amount: amount("100"),
+                },
+                amount("772.62"),
+                Amount::ZERO,
+            ),
+            (
+                AppliedEffect::Held {
+                    client: 2,
+                    tx: 200,
+                    amount: amount("472.6178"),
+                },
+                amount("300.0022"),
+                amount("472.6178"),
+            ),
+            (
+                AppliedEffect::Reversed {
+                    client: 2,
+                    tx: 200,
+                    amount: amount("472.6178"),
+                },
+                amount("300.0022"),
+                Amount::ZERO,
+            ),
+        ],
+        actual
+    );
+    assert_eq!(3, bank.history(1).len());
+    assert_eq!(amount("300.28"), bank.history(1)[2].total);
+    assert!(bank.history(3).is_empty());
+}
+
+#[test]
+fn it_looks_up_a_stored_transaction_by_its_tx() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    // Act
+    bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 200,
+        amount: None,
+    })
+    .unwrap();
+    // Assert
+    assert_eq!(
+        Some(&Transaction::Deposit {
+            client: 2,
+            tx: 200,
+            amount: Amount::from_str("472.6178").unwrap(),
+            state: DisputeState::Disputed,
+            disputed: Amount::from_str("472.6178").unwrap(),
+            charged_back: Amount::ZERO,
+        }),
+        bank.transaction(200)
+    );
+    assert_eq!(None, bank.transaction(201));
+}
+
+#[test]
+fn it_verifies_the_invariants_of_every_account_after_disputes_and_chargebacks() {
+    // Arrange
+    let mut bank = Bank::default().with_dispute_policy(DisputePolicy::PendingCredit);
+    apply_test_deposits(&mut bank);
+    apply_test_withdrawal(&mut bank);
+    bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 2000,
+        amount: None,
+    })
+    .unwrap();
+    bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 100,
+        amount: Some(Amount::from_str("100").unwrap()),
+    })
+    .unwrap();
+    lock_test_account(&mut bank);
+    // Act
+    let violations = bank.verify();
+    // Assert
+    assert_eq!(Vec::<Violation>::new(), violations);
+}
+
+#[test]
+fn it_reports_disputed_sums_that_overflow_instead_of_panicking() {
+    // Arrange
+    let disputed = |tx: u32| Transaction::Deposit {
+        client: 1,
+        tx,
+        amount: Amount::from_str("900000000000000").unwrap(),
+        state: DisputeState::Disputed,
+        disputed: Amount::from_str("900000000000000").unwrap(),
+        charged_back: Amount::ZERO,
+    };
+    let bank = Bank::restore(Snapshot {
+        accounts: vec![Account {
+            client: 1,
+            available: Amount::ZERO,
+            held: Amount::from_str("900000000000000").unwrap(),
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        }],
+        transactions: vec![disputed(1), disputed(2)],
+        ..Snapshot::default()
+    })
+    .unwrap();
+    // Act
+    let violations = bank.verify();
+    // Assert
+    assert_eq!(vec![Violation::Overflow { client: 1 }], violations);
+}
+
+#[test]
+fn it_reports_the_broken_invariants_of_a_corrupted_snapshot() {
+    // Arrange
+    let bank = Bank::restore(Snapshot {
+        accounts: vec![Account {
+            client: 1,
+            available: Amount::from_str("10").unwrap(),
+            held: Amount::from_str("5").unwrap(),
+            status: AccountStatus::Locked,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::from_str("1").unwrap(),
+        }],
+        transactions: vec![Transaction::Deposit {
+            client: 1,
+            tx: 1,
+            amount: Amount::from_str("15").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::from_str("2").unwrap(),
+            charged_back: Amount::ZERO,
+        }],
+        ..Snapshot::default()
+    })
+    .unwrap();
+    // Act
+    let violations = bank.verify();
+    // Assert
+    assert_eq!(
+        vec![
+            Violation::DisputeStateMismatch {
+                client: 1,
+                tx: 1,
+                state: DisputeState::Settled,
+                disputed: Amount::from_str("2").unwrap(),
+            },
+            Violation::HeldMismatch {
+                client: 1,
+                held: Amount::from_str("5").unwrap(),
+                disputed: Amount::from_str("2").unwrap(),
+            },
+            Violation::AuthorizedMismatch {
+                client: 1,
+                authorized: Amount::from_str("1").unwrap(),
+                open: Amount::ZERO,
+            },
+            Violation::LockedWithoutChargeback { client: 1 },
+        ],
+        violations
+    );
+}
+
+#[test]
+fn it_merges_a_bank_split_by_client_back_into_the_same_bank() {
+    // Arrange
+    let mut bank = Bank::default().with_fee_schedule(FeeSchedule {
+        chargeback: Amount::from_str("15").unwrap(),
+        ..FeeSchedule::default()
+    });
+    apply_test_deposits(&mut bank);
+    apply_test_withdrawal(&mut bank);
+    lock_test_account(&mut bank);
+    let snapshot = bank.snapshot();
+    // Act
+    let shards = Bank::restore(snapshot.clone())
+        .unwrap()
+        .split(3, |client| client as usize % 3);
+    let accounts = shards
+        .iter()
+        .map(|shard| shard.balances().count())
+        .collect::<Vec<_>>();
+    let merged = Bank::merge(shards).unwrap();
+    // Assert
+    assert_eq!(vec![0, 1, 1], accounts);
+    assert_eq!(Bank::restore(snapshot).unwrap(), merged);
+    assert!(merged.ledger().is_balanced());
+}
+
+#[test]
+fn it_rejects_a_deposit_that_would_overflow_the_balance() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    // Act
+    let rejection = bank
+        .apply(Transaction::Deposit {
+            client: 2,
+            tx: 3000,
+            amount: Amount::from_scaled(i64::MAX),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        })
+        .unwrap_err();
+    let mut actual = bank.balances().collect::<Vec<_>>();
+    actual.sort_by_key(|v| v.client);
+    // Assert
+    assert_eq!(
+        Rejection::Overflow {
+            client: 2,
+            tx: 3000
+        },
+        rejection
+    );
+    assert_eq!(Amount::from_str("672.62").unwrap(), actual[1].available);
+}
+
+#[test]
+fn it_rejects_or_rounds_amounts_with_excess_precision_according_to_the_precision_policy() {
+    // Arrange
+    let reject = PrecisionPolicy {
+        max_input_scale: 2,
+        ..PrecisionPolicy::default()
+    };
+    let round = PrecisionPolicy {
+        excess: ExcessPrecision::Round,
+        ..reject
+    };
+    let mut rejecting = Bank::default().with_precision_policy(reject);
+    let mut rounding = Bank::default().with_precision_policy(round);
+    let deposit = Transaction::Deposit {
+        client: 1,
+        tx: 1,
+        amount: Amount::from_str("100.0057").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    };
+    // Act
+    let rejected = rejecting.apply(deposit.clone());
+    let rounded = rounding.apply(deposit);
+    // Assert
+    assert_eq!(
+        Err(Rejection::ExcessPrecision { client: 1, tx: 1 }),
+        rejected
+    );
+    assert_eq!(
+        Ok(AppliedEffect::Credited {
+            client: 1,
+            tx: 1,
+            amount: Amount::from_str("100.01").unwrap(),
+        }),
+        rounded
+    );
+    assert_eq!(0, rejecting.balances().count());
+}
+
+#[test]
+fn it_rejects_a_negative_withdrawal_so_it_cannot_inflate_available_funds() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    // Act
+    let rejection = bank
+        .apply(Transaction::Withdrawal {
+            client: 2,
+            tx: 2000,
+            amount: Amount::from_str("-500.00").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        })
+        .unwrap_err();
+    let mut actual = bank.balances().collect::<Vec<_>>();
+    actual.sort_by_key(|v| v.client);
+    // Assert
+    assert_eq!(
+        Rejection::NonPositiveAmount {
+            client: 2,
+            tx: 2000
+        },
+        rejection
+    );
+    assert_eq!(Amount::from_str("672.62").unwrap(), actual[1].available);
+}
+
+#[test]
+fn it_rejects_negative_and_zero_deposits() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    // Act
+    let actual = ["-500.00", "0"].map(|amount| {
+        bank.apply(Transaction::Deposit {
+            client: 3,
+            tx: 3000,
+            amount: Amount::from_str(amount).unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        })
+    });
+    // Assert
+    for actual in actual {
+        assert_eq!(
+            Err(Rejection::NonPositiveAmount {
+                client: 3,
+                tx: 3000
+            }),
+            actual
+        );
+    }
+    assert_eq!(2, bank.balances().count());
+}
+
+#[test]
+fn it_never_charges_back_a_transaction_twice() {
+    // Arrange
+    let mut bank = Bank::default().with_freeze_policy(FreezePolicy::AllowDisputes);
+    apply_test_deposits(&mut bank);
+    lock_test_account(&mut bank);
+    // Act
+    let dispute = bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 200,
+        amount: None,
+    });
+    let resolve = bank.apply(Transaction::Resolve {
+        client: 2,
+        referenced_tx: 200,
+        amount: None,
+    });
+    let restored = Bank::restore(bank.snapshot())
+        .unwrap()
+        .with_freeze_policy(FreezePolicy::AllowDisputes)
+        .apply(Transaction::Dispute {
+            client: 2,
+            referenced_tx: 200,
+            amount: None,
+        });
+    // Assert
+    for actual in [dispute, resolve, restored] {
+        assert_eq!(
+            Err(Rejection::AlreadyChargedBack {
+                client: 2,
+                referenced_tx: 200
+            }),
+            actual
+        );
+    }
+    assert_eq!(
+        Some(&Transaction::Deposit {
+            client: 2,
+            tx: 200,
+            amount: Amount::from_str("472.6178").unwrap(),
+            state: DisputeState::ChargedBack,
+            disputed: Amount::ZERO,
+            charged_back: Amount::from_str("472.6178").unwrap(),
+        }),
+        bank.snapshot().transactions.iter().find(|t| t.tx() == 200)
+    );
+}
+
+#[test]
+fn it_allows_a_resolved_transaction_to_be_disputed_again() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    let dispute = Transaction::Dispute {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    };
+    bank.apply(dispute.clone()).unwrap();
+    bank.apply(Transaction::Resolve {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    })
+    .unwrap();
+    // Act
+    let actual = bank.apply(dispute);
+    // Assert
+    assert_eq!(
+        Ok(AppliedEffect::Held {
+            client: 1,
+            tx: 100,
+            amount: Amount::from_str("300.2743").unwrap(),
+        }),
+        actual
+    );
+}
+
+#[test]
+fn it_follows_the_dispute_state_transition_table() {
+    // Arrange
+    let states = [
+        DisputeState::Settled,
+        DisputeState::Disputed,
+        DisputeState::Resolved,
+        DisputeState::ChargedBack,
+    ];
+    let (none, part) = (Amount::ZERO, Amount::from_str("1.00").unwrap());
+    let expected = [
+        (Some(DisputeState::Disputed), None, None, None, None),
+        (
+            Some(DisputeState::Disputed),
+            Some(DisputeState::Resolved),
+            Some(DisputeState::Disputed),
+            Some(DisputeState::ChargedBack),
+            Some(DisputeState::Disputed),
+        ),
+        (Some(DisputeState::Disputed), None, None, None, None),
+        (None, None, None, None, None),
+    ];
+    // Act
+    let actual = states.map(|state| {
+        (
+            state.disputed(),
+            state.resolved(none),
+            state.resolved(part),
+            state.charged_back(none),
+            state.charged_back(part),
+        )
+    });
+    // Assert
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn it_rejects_disputes_of_withdrawals_with_the_disallow_dispute_policy() {
+    // Arrange
+    let mut bank = Bank::default().with_dispute_policy(DisputePolicy::Disallow);
+    apply_test_deposits(&mut bank);
+    apply_test_withdrawal(&mut bank);
+    // Act
+    let dispute = bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 2000,
+        amount: None,
+    });
+    let resolve = bank.apply(Transaction::Resolve {
+        client: 2,
+        referenced_tx: 2000,
+        amount: None,
+    });
+    let chargeback = bank.apply(Transaction::ChargeBack {
+        client: 2,
+        referenced_tx: 2000,
+        amount: None,
+    });
+    let deposit_dispute = bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 200,
+        amount: None,
+    });
+    // Assert
+    assert_eq!(
+        Err(Rejection::WithdrawalDisputeDisallowed {
+            client: 2,
+            referenced_tx: 2000
+        }),
+        dispute
+    );
+    for actual in [resolve, chargeback] {
+        assert_eq!(
+            Err(Rejection::NotDisputed {
+                client: 2,
+                referenced_tx: 2000
+            }),
+            actual
+        );
+    }
+    assert!(deposit_dispute.is_ok());
+}
+
+#[test]
+fn it_refunds_disputed_withdrawals_to_available_with_the_refund_to_available_dispute_policy() {
+    // Arrange
+    let mut bank = Bank::default().with_dispute_policy(DisputePolicy::RefundToAvailable);
+    apply_test_deposits(&mut bank);
+    apply_test_withdrawal(&mut bank);
+    let dispute = Transaction::Dispute {
+        client: 2,
+        referenced_tx: 2000,
+        amount: None,
+    };
+    let withdrawn = Amount::from_str("72.6052").unwrap();
+    let disputed = withdrawn.checked_neg().unwrap();
+    // Act
+    let held = bank.apply(dispute.clone()).unwrap();
+    let after_dispute = test_account(&bank, 2);
+    let released = bank
+        .apply(Transaction::Resolve {
+            client: 2,
+            referenced_tx: 2000,
+            amount: None,
+        })
+        .unwrap();
+    let after_resolve = test_account(&bank, 2);
+    bank.apply(dispute).unwrap();
+    let reversed = bank
+        .apply(Transaction::ChargeBack {
+            client: 2,
+            referenced_tx: 2000,
+            amount: None,
+        })
+        .unwrap();
+    let after_chargeback = test_account(&bank, 2);
+    // Assert
+    assert_eq!(
+        AppliedEffect::Held {
+            client: 2,
+            tx: 2000,
+            amount: disputed,
+        },
+        held
+    );
+    assert_eq!(
+        (Amount::from_str("672.62").unwrap(), disputed),
+        (after_dispute.available, after_dispute.held)
+    );
+    assert_eq!(
+        AppliedEffect::Released {
+            client: 2,
+            tx: 2000,
+            amount: disputed,
+        },
+        released
+    );
+    assert_eq!(
+        (Amount::from_str("600.0148").unwrap(), Amount::ZERO),
+        (after_resolve.available, after_resolve.held)
+    );
+    assert_eq!(
+        AppliedEffect::Reversed {
+            client: 2,
+            tx: 2000,
+            amount: disputed,
+        },
+        reversed
+    );
+    assert_eq!(
+        (
+            Amount::from_str("672.62").unwrap(),
+            Amount::ZERO,
+            AccountStatus::Locked
+        ),
+        (
+            after_chargeback.available,
+            after_chargeback.held,
+            after_chargeback.status
+        )
+    );
+}
+
+#[test]
+fn it_holds_disputed_withdrawals_as_a_pending_credit_with_the_pending_credit_dispute_policy() {
+    // Arrange
+    let mut bank = Bank::default().with_dispute_policy(DisputePolicy::PendingCredit);
+    apply_test_deposits(&mut bank);
+    apply_test_withdrawal(&mut bank);
+    let dispute = Transaction::Dispute {
+        client: 2,
+        referenced_tx: 2000,
+        amount: None,
+    };
+    let withdrawn = Amount::from_str("72.6052").unwrap();
+    let remaining = Amount::from_str("600.0148").unwrap();
+    // Act
+    let raised = bank.apply(dispute.clone()).unwrap();
+    let after_dispute = test_account(&bank, 2);
+    let dropped = bank
+        .apply(Transaction::Resolve {
+            client: 2,
+            referenced_tx: 2000,
+            amount: None,
+        })
+        .unwrap();
+    let after_resolve = test_account(&bank, 2);
+    bank.apply(dispute).unwrap();
+    let granted = bank
+        .apply(Transaction::ChargeBack {
+            client: 2,
+            referenced_tx: 2000,
+            amount: None,
+        })
+        .unwrap();
+    let after_chargeback = test_account(&bank, 2);
+    // Assert
+    assert_eq!(
+        AppliedEffect::PendingRaised {
+            client: 2,
+            tx: 2000,
+            amount: withdrawn,
+        },
+        raised
+    );
+    assert_eq!(
+        Account {
+            client: 2,
+            available: remaining,
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: withdrawn,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        },
+        after_dispute
+    );
+    assert_eq!(
+        AppliedEffect::PendingDropped {
+            client: 2,
+            tx: 2000,
+            amount: withdrawn,
+        },
+        dropped
+    );
+    assert_eq!(
+        Account {
+            client: 2,
+            available: remaining,
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        },
+        after_resolve
+    );
+    assert_eq!(
+        AppliedEffect::PendingGranted {
+            client: 2,
+            tx: 2000,
+            amount: withdrawn,
+        },
+        granted
+    );
+    assert_eq!(
+        Account {
+            client: 2,
+            available: Amount::from_str("672.62").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Locked,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        },
+        after_chargeback
+    );
+}
+
+#[test]
+fn it_rejects_a_dispute_of_a_withdrawn_deposit_with_the_reject_shortfall_policy() {
+    // Arrange
+    let mut bank = Bank::default().with_shortfall_policy(ShortfallPolicy::Reject);
+    apply_test_deposits(&mut bank);
+    withdraw_test_deposit(&mut bank);
+    // Act
+    let actual = bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    });
+    let covered = bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 1,
+        amount: None,
+    });
+    // Assert
+    assert_eq!(
+        Err(Rejection::FundsAlreadyWithdrawn {
+            client: 1,
+            referenced_tx: 100
+        }),
+        actual
+    );
+    assert!(covered.is_ok());
+}
+
+#[test]
+fn it_allows_negative_available_funds_on_a_dispute_of_a_withdrawn_deposit_by_default() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    withdraw_test_deposit(&mut bank);
+    // Act
+    bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    })
+    .unwrap();
+    // Assert
+    assert_eq!(
+        Account {
+            client: 1,
+            available: Amount::from_str("-200.2686").unwrap(),
+            held: Amount::from_str("300.2743").unwrap(),
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        },
+        test_account(&bank, 1)
+    );
+}
+
+#[test]
+fn it_flags_the_account_on_a_dispute_of_a_withdrawn_deposit_with_the_flag_shortfall_policy() {
+    // Arrange
+    let mut bank = Bank::default().with_shortfall_policy(ShortfallPolicy::Flag);
+    apply_test_deposits(&mut bank);
+    withdraw_test_deposit(&mut bank);
+    // Act
+    let effect = bank
+        .apply(Transaction::Dispute {
+            client: 1,
+            referenced_tx: 100,
+            amount: None,
+        })
+        .unwrap();
+    bank.apply(Transaction::Resolve {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    })
+    .unwrap();
+    // Assert
+    assert_eq!(
+        AppliedEffect::HeldFlagged {
+            client: 1,
+            tx: 100,
+            amount: Amount::from_str("300.2743").unwrap(),
+        },
+        effect
+    );
+    assert_eq!(
+        Account {
+            client: 1,
+            available: Amount::from_str("100.0057").unwrap(),
+            held: Amount::ZERO,
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: true,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        },
+        test_account(&bank, 1)
+    );
+}
+
+#[test]
+fn it_moves_the_shortfall_into_receivable_with_the_receivable_shortfall_policy() {
+    // Arrange
+    let mut bank = Bank::default().with_shortfall_policy(ShortfallPolicy::Receivable);
+    apply_test_deposits(&mut bank);
+    withdraw_test_deposit(&mut bank);
+    let dispute = Transaction::Dispute {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    };
+    // Act
+    bank.apply(dispute.clone()).unwrap();
+    let after_dispute = test_account(&bank, 1);
+    bank.apply(Transaction::Resolve {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    })
+    .unwrap();
+    let after_resolve = test_account(&bank, 1);
+    bank.apply(dispute).unwrap();
+    bank.apply(Transaction::ChargeBack {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    })
+    .unwrap();
+    let after_chargeback = test_account(&bank, 1);
+    // Assert
+    assert_eq!(
+        (
+            Amount::ZERO,
+            Amount::from_str("300.2743").unwrap(),
+            Amount::from_str("200.2686").unwrap()
+        ),
+        (
+            after_dispute.available,
+            after_dispute.held,
+            after_dispute.receivable
+        )
+    );
+    assert_eq!(
+        (
+            Amount::from_str("100.0057").unwrap(),
+            Amount::ZERO,
+            Amount::ZERO
+        ),
+        (
+            after_resolve.available,
+            after_resolve.held,
+            after_resolve.receivable
+        )
+    );
+    assert_eq!(
+        (
+            Amount::ZERO,
+            Amount::ZERO,
+            Amount::from_str("200.2686").unwrap(),
+            AccountStatus::Locked
+        ),
+        (
+            after_chargeback.available,
+            after_chargeback.held,
+            after_chargeback.receivable,
+            after_chargeback.status
+        )
+    );
+}
+
+#[test]
+fn it_allows_multiple_partial_disputes_up_to_the_original_amount() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    let dispute = |amount: &str| Transaction::Dispute {
+        client: 1,
+        referenced_tx: 100,
+        amount: Some(Amount::from_str(amount).unwrap()),
+    };
+    // Act
+    let first = bank.apply(dispute("100.0000"));
+    let second = bank.apply(dispute("200.2743"));
+    let exceeded = bank.apply(dispute("0.0001"));
+    // Assert
+    assert_eq!(
+        Ok(AppliedEffect::Held {
+            client: 1,
+            tx: 100,
+            amount: Amount::from_str("100").unwrap(),
+        }),
+        first
+    );
+    assert!(second.is_ok());
+    assert_eq!(
+        Err(Rejection::AlreadyDisputed {
+            client: 1,
+            referenced_tx: 100
+        }),
+        exceeded
+    );
+    assert_eq!(
+        (
+            Amount::from_str("100.0057").unwrap(),
+            Amount::from_str("300.2743").unwrap()
+        ),
+        (
+            test_account(&bank, 1).available,
+            test_account(&bank, 1).held
+        )
+    );
+}
+
+#[test]
+fn it_rejects_a_partial_dispute_resolve_or_chargeback_exceeding_the_disputable_amount() {
+    // Arrange
+    let mut bank = Bank::default();
+    apply_test_deposits(&mut bank);
+    let amount = Some(Amount::from_str("100").unwrap());
+    bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 100,
+        amount,
+    })
+    .unwrap();
+    let exceeding = Some(Amount::from_str("100.0001").unwrap());
+    // Act
+    let dispute = bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 100,
+        amount: Some(Amount::from_str("200.2744").unwrap()),
+    });
+    let resolve = bank.apply(Transaction::Resolve {
+        client: 1,
+        referenced_tx: 100,
+        amount: exceeding,
+    });
+    let chargeback = bank.apply(Transaction::ChargeBack {
+        client: 1,
+        referenced_tx: 100,
+        amount: exceeding,
+    });
+    let negative = bank.apply(Transaction::Resolve {
+        client: 1,
+        referenced_tx: 100,
+        amount: Some(Amount::from_str("-1").unwrap()),
+    });
+    // Assert
+    for actual in [dispute, resolve, chargeback] {
+        assert_eq!(
+            Err(Rejection::DisputeAmountExceeded {
+                client: 1,
+                referenced_tx: 100
+            }),
+            actual
+        );
+    }
+    assert_eq!(
+        Err(Rejection::NonPositiveAmount { client: 1, tx: 100 }),
+        negative
+    );
+}
+
+#[test]
+fn it_handles_partial_resolves_and_chargebacks_of_a_disputed_deposit() {
+    // Arrange
+    let mut bank = Bank::default().with_freeze_policy(FreezePolicy::AllowDisputes);
+    apply_test_deposits(&mut bank);
+    bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    })
+    .unwrap();
+    // Act
+    let reversed = bank.apply(Transaction::ChargeBack {
+        client: 1,
+        referenced_tx: 100,
+        amount: Some(Amount::from_str("100.2743").unwrap()),
+    });
+    let still_disputed = stored_test_transaction(&bank, 100);
+    let released = bank.apply(Transaction::Resolve {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    });
+    let redispute = bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 100,
+        amount: None,
+    });
+    // Assert
+    assert!(reversed.is_ok());
+    assert_eq!(
+        Transaction::Deposit {
+            client: 1,
+            tx: 100,
+            amount: Amount::from_str("300.2743").unwrap(),
+            state: DisputeState::Disputed,
+            disputed: Amount::from_str("200").unwrap(),
+            charged_back: Amount::from_str("100.2743").unwrap(),
+        },
+        still_disputed
+    );
+    assert_eq!(
+        Ok(AppliedEffect::Released {
+            client: 1,
+            tx: 100,
+            amount: Amount::from_str("200").unwrap(),
+        }),
+        released
+    );
+    assert_eq!(
+        Ok(AppliedEffect::Held {
+            client: 1,
+            tx: 100,
+            amount: Amount::from_str("200").unwrap(),
+        }),
+        redispute,
+        "only the part that was not charged back can be disputed again"
+    );
+    assert_eq!(
+        Account {
+            client: 1,
+            available: Amount::from_str("100.0057").unwrap(),
+            held: Amount::from_str("200").unwrap(),
+            status: AccountStatus::Locked,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        },
+        test_account(&bank, 1)
+    );
+}
+
+fn apply_test_deposits(bank: &mut Bank) {
+    bank.apply(Transaction::Deposit {
+        client: 1,
+        tx: 1,
+        amount: Amount::from_str("100.0057").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    bank.apply(Transaction::Deposit {
+        client: 2,
+        tx: 2,
+        amount: Amount::from_str("200.0022").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    bank.apply(Transaction::Deposit {
+        client: 1,
+        tx: 100,
+        amount: Amount::from_str("300.2743").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+    bank.apply(Transaction::Deposit {
+        client: 2,
+        tx: 200,
+        amount: Amount::from_str("472.6178").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+}
+
+fn apply_test_withdrawal(bank: &mut Bank) {
+    bank.apply(Transaction::Withdrawal {
+        client: 2,
+        tx: 2000,
+        amount: Amount::from_str("72.6052").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+}
+
+fn withdraw_test_deposit(bank: &mut Bank) {
+    bank.apply(Transaction::Withdrawal {
+        client: 1,
+        tx: 1000,
+        amount: Amount::from_str("300.2743").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+    .unwrap();
+}
+
+fn stored_test_transaction(bank: &Bank, tx: u32) -> Transaction {
+    bank.snapshot()
+        .transactions
+        .into_iter()
+        .find(|transaction| transaction.tx() == tx)
+        .unwrap()
+}
+
+fn test_account(bank: &Bank, client: u16) -> Account {
+    bank.balances()
+        .find(|acct| acct.client == client)
+        .cloned()
+        .unwrap()
+}
+
+fn lock_test_account(bank: &mut Bank) {
+    bank.apply(Transaction::Dispute {
+        client: 2,
+        referenced_tx: 200,
+        amount: None,
+    })
+    .unwrap();
+    bank.apply(Transaction::ChargeBack {
+        client: 2,
+        referenced_tx: 200,
+        amount: None,
+    })
+    .unwrap();
 }
@@ -0,0 +1,255 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::amount::Amount;
+
+use super::{
+    ledger::LedgerAccount, policy::DisputePolicy, AccountStatus, Bank, DisputeState, Transaction,
+};
+
+// An invariant of the bank found broken after processing.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Violation {
+    UnbalancedLedger,
+    TotalMismatch {
+        client: u16,
+        total: Amount,
+        ledger_total: Amount,
+    },
+    HeldMismatch {
+        client: u16,
+        held: Amount,
+        disputed: Amount,
+    },
+    PendingCreditMismatch {
+        client: u16,
+        pending_credit: Amount,
+        disputed: Amount,
+    },
+    AuthorizedMismatch {
+        client: u16,
+        authorized: Amount,
+        open: Amount,
+    },
+    DisputeStateMismatch {
+        client: u16,
+        tx: u32,
+        state: DisputeState,
+        disputed: Amount,
+    },
+    LockedWithoutChargeback {
+        client: u16,
+    },
+    Overflow {
+        client: u16,
+    },
+}
+
+#[derive(Clone, Copy, Default)]
+struct Disputed {
+    held: Amount,
+    pending_credit: Amount,
+    charged_back: bool,
+}
+
+impl Bank {
+    // Checks the balances of every account against the ledger, the disputed
+    // transactions and the open authorizations, returning what is broken.
+    pub fn verify(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        if !self.ledger.is_balanced() {
+            violations.push(Violation::UnbalancedLedger);
+        }
+        let mut transactions = self.transactions.values().collect::<Vec<_>>();
+        transactions.sort_by_key(|transaction| transaction.tx());
+        let mut disputed = HashMap::<u16, Disputed>::new();
+        let mut overflowed = HashSet::<u16>::new();
+        for transaction in transactions {
+            let (client, holder, tx, state, amount, charged_back) = match *transaction {
+                Transaction::Deposit {
+                    client,
+                    tx,
+                    state,
+                    disputed,
+                    charged_back,
+                    ..
+                } => (client, client, tx, state, disputed, charged_back),
+                Transaction::Withdrawal {
+                    client,
+                    tx,
+                    state,
+                    disputed,
+                    charged_back,
+                    ..
+                } => match disputed.checked_neg() {
+                    Ok(disputed) => (client, client, tx, state, disputed, charged_back),
+                    Err(_) => {
+                        overflowed.insert(client);
+                        continue;
+                    }
+                },
+                Transaction::Transfer {
+                    client,
+                    to,
+                    tx,
+                    state,
+                    disputed,
+                    charged_back,
+                    ..
+                } => (client, to, tx, state, disputed, charged_back),
+                _ => continue,
+            };
+            if (state == DisputeState::Disputed) != (amount != Amount::ZERO) {
+                violations.push(Violation::DisputeStateMismatch {
+                    client,
+                    tx,
+                    state,
+                    disputed: amount.abs(),
+                });
+            }
+            let sums = disputed.entry(holder).or_default();
+            let summed =
+                match amount.is_negative() && self.dispute_policy == DisputePolicy::PendingCredit {
+                    true => (sums.pending_credit.checked_sub(amount))
+                        .map(|sum| sums.pending_credit = sum),
+                    false => (sums.held.checked_add(amount)).map(|sum| sums.held = sum),
+                };
+            if summed.is_err() {
+                overflowed.insert(holder);
+            }
+            if charged_back != Amount::ZERO {
+                sums.charged_back = true;
+                disputed.entry(client).or_default().charged_back = true;
+            }
+        }
+        let mut open = HashMap::<u16, Amount>::new();
+        for auth in self.authorizations.values() {
+            let sum = open.entry(auth.client).or_default();
+            match sum.checked_add(auth.amount) {
+                Ok(total) => *sum = total,
+                Err(_) => {
+                    overflowed.insert(auth.client);
+                }
+            }
+        }
+        let mut accounts = self.accounts.values().collect::<Vec<_>>();
+        accounts.sort_by_key(|acct| acct.client);
+        for acct in accounts {
+            let client = acct.client;
+            let ledger_total = [
+                LedgerAccount::Available(client),
+                LedgerAccount::Held(client),
+                LedgerAccount::Authorized(client),
+            ]
+            .into_iter()
+            .try_fold(Amount::ZERO, |total, account| {
+                total.checked_add(self.ledger.balance(account))
+            })
+            .and_then(Amount::checked_neg);
+            let total = (acct.available.checked_add(acct.held))
+                .and_then(|total| total.checked_add(acct.authorized));
+            // Sums that overflow cannot be compared with the balances.
+            let (total, ledger_total) = match (total, ledger_total) {
+                (Ok(total), Ok(ledger_total)) if !overflowed.contains(&client) => {
+                    (total, ledger_total)
+                }
+                _ => {
+                    violations.push(Violation::Overflow { client });
+                    continue;
+                }
+            };
+            if total != ledger_total {
+                violations.push(Violation::TotalMismatch {
+                    client,
+                    total,
+                    ledger_total,
+                });
+            }
+            let sums = disputed.get(&client).copied().unwrap_or_default();
+            if acct.held != sums.held {
+                violations.push(Violation::HeldMismatch {
+                    client,
+                    held: acct.held,
+                    disputed: sums.held,
+                });
+            }
+            if acct.pending_credit != sums.pending_credit {
+                violations.push(Violation::PendingCreditMismatch {
+                    client,
+                    pending_credit: acct.pending_credit,
+                    disputed: sums.pending_credit,
+                });
+            }
+            let open = open.get(&client).copied().unwrap_or_default();
+            if acct.authorized != open {
+                violations.push(Violation::AuthorizedMismatch {
+                    client,
+                    authorized: acct.authorized,
+                    open,
+                });
+            }
+            if acct.status == AccountStatus::Locked && !sums.charged_back {
+                violations.push(Violation::LockedWithoutChargeback { client });
+            }
+        }
+        violations
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::UnbalancedLedger => write!(f, "ledger debits do not equal credits"),
+            Violation::TotalMismatch {
+                client,
+                total,
+                ledger_total,
+            } => write!(
+                f,
+                "client {client} has a total of {total} but its ledger accounts total {ledger_total}"
+            ),
+            Violation::HeldMismatch {
+                client,
+                held,
+                disputed,
+            } => write!(
+                f,
+                "client {client} holds {held} but has {disputed} disputed"
+            ),
+            Violation::PendingCreditMismatch {
+                client,
+                pending_credit,
+                disputed,
+            } => write!(
+                f,
+                "client {client} has a pending credit of {pending_credit} but {disputed} of withdrawals disputed"
+            ),
+            Violation::AuthorizedMismatch {
+                client,
+                authorized,
+                open,
+            } => write!(
+                f,
+                "client {client} has {authorized} authorized but {open} in open authorizations"
+            ),
+            Violation::DisputeStateMismatch {
+                client,
+                tx,
+                state,
+                disputed,
+            } => write!(
+                f,
+                "tx {tx} of client {client} is {} with {disputed} disputed",
+                state.as_str()
+            ),
+            Violation::LockedWithoutChargeback { client } => {
+                write!(f, "client {client} is locked without a chargeback")
+            }
+            Violation::Overflow { client } => {
+                write!(f, "the balances of client {client} overflow when summed")
+            }
+        }
+    }
+}
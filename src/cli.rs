@@ -1,9 +1,63 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use simple_xact::{
+    amount::{ExcessPrecision, RoundingMode},
+    bank::policy::{DayCount, DisputePolicy, FreezePolicy, ShortfallPolicy},
+    date::Date,
+};
 
 #[derive(Parser, Debug)]
 pub struct Arguments {
     #[clap(required = true, parse(from_os_str))]
     pub path: PathBuf,
+    #[clap(long, default_value = "reject-all")]
+    pub freeze_policy: FreezePolicy,
+    #[clap(long, default_value = "refund-to-available")]
+    pub dispute_policy: DisputePolicy,
+    #[clap(long, default_value = "allow-negative")]
+    pub shortfall_policy: ShortfallPolicy,
+    #[clap(long)]
+    pub flag_client_mismatch: bool,
+    #[clap(long, default_value = "4")]
+    pub max_scale: u32,
+    #[clap(long, default_value = "reject")]
+    pub excess_precision: ExcessPrecision,
+    #[clap(long, default_value = "half-even")]
+    pub rounding: RoundingMode,
+    #[clap(long, default_value = "4")]
+    pub output_scale: u32,
+    #[clap(long, parse(from_os_str))]
+    pub fee_rules: Option<PathBuf>,
+    #[clap(long)]
+    pub authorization_expiry: Option<u64>,
+    #[clap(long, parse(from_os_str))]
+    pub interest_rules: Option<PathBuf>,
+    #[clap(long, default_value = "actual-365")]
+    pub day_count: DayCount,
+    #[clap(long)]
+    pub accrue_as_of: Option<Date>,
+    #[clap(long, default_value = "1")]
+    pub threads: usize,
+    #[clap(long, parse(from_os_str))]
+    pub rejects: Option<PathBuf>,
+    #[clap(long, parse(from_os_str))]
+    pub journal: Option<PathBuf>,
+    #[clap(long, parse(from_os_str))]
+    pub trial_balance: Option<PathBuf>,
+    #[clap(long)]
+    pub verify: bool,
+    #[clap(long, parse(from_os_str), conflicts_with = "load-snapshot")]
+    pub replay: Option<PathBuf>,
+    #[clap(long, parse(from_os_str))]
+    pub load_snapshot: Option<PathBuf>,
+    #[clap(long, parse(from_os_str))]
+    pub save_snapshot: Option<PathBuf>,
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    Statement { client: u16 },
 }
@@ -0,0 +1,43 @@
+use std::str::FromStr;
+
+use super::{Date, DateError};
+
+#[test]
+fn it_parses_and_formats_dates() {
+    // Arrange
+    let inputs = ["2024-01-31", "2024-02-29", "1999-12-01"];
+    // Act
+    let actual = inputs.map(|input| Date::from_str(input).map(|date| date.to_string()));
+    // Assert
+    assert_eq!(inputs.map(|input| Ok(input.to_string())), actual);
+}
+
+#[test]
+fn it_rejects_malformed_and_impossible_dates() {
+    // Arrange
+    let inputs = [
+        "",
+        "2024-1-31",
+        "2024-01-31-01",
+        "24-01-31",
+        "2024-13-01",
+        "2023-02-29",
+        "2024-04-31",
+        "2024-01-00",
+    ];
+    // Act
+    let actual = inputs.map(Date::from_str);
+    // Assert
+    assert_eq!(inputs.map(|_| Err(DateError)), actual);
+}
+
+#[test]
+fn it_counts_days_since_the_epoch_across_leap_years() {
+    // Arrange
+    let inputs = ["1970-01-01", "2000-03-01", "2024-02-29", "1969-12-31"];
+    let expected = [0, 11_017, 19_782, -1];
+    // Act
+    let actual = inputs.map(|input| Date::from_str(input).unwrap().days_since_epoch());
+    // Assert
+    assert_eq!(expected, actual);
+}
@@ -0,0 +1,271 @@
+use std::{
+    collections::HashMap,
+    error, mem,
+    sync::mpsc::{self, Receiver, SyncSender},
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    bank::{AppliedEffect, Bank, Rejection, Transaction},
+    io::read::{ParseError, RawRecord},
+};
+
+#[cfg(test)]
+mod tests;
+
+const BATCH_SIZE: usize = 1024;
+const BATCHES_IN_FLIGHT: usize = 16;
+
+// What became of an input row, or of the authorizations that expired before
+// it, in the order of the input.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Outcome {
+    Applied(Vec<AppliedEffect>),
+    Rejected(RawRecord, Rejection),
+    Unparsed(ParseError),
+}
+
+// The outcomes handed on to be recorded; the others are dropped as soon as
+// the row is applied, and shards do not keep the raw records of their rows.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct Recorded {
+    pub applied: bool,
+    pub rejected: bool,
+}
+
+impl Recorded {
+    pub const ALL: Recorded = Recorded {
+        applied: true,
+        rejected: true,
+    };
+
+    fn keeps(self, outcome: &Outcome) -> bool {
+        match outcome {
+            Outcome::Applied(_) => self.applied,
+            Outcome::Rejected(..) | Outcome::Unparsed(_) => self.rejected,
+        }
+    }
+}
+
+type Row = (usize, Option<RawRecord>, Transaction);
+type Processed = (Bank, Vec<(usize, Outcome)>);
+
+// Applies the rows of an input to a bank, on one thread or on worker threads
+// each owning the shard of the bank holding the clients with the same
+// remainder by the number of threads. A row that needs the state of more than
+// one shard (a transfer between shards, a tx id already used in another shard,
+// or an authorization, which is stamped with the sequence of the whole bank)
+// merges the shards again and the rest of the input is applied on one thread,
+// so the outcome is the same as applying every row in order on one bank.
+// Outcomes are recorded in the order of the input: on one thread as each row
+// is applied, and from shards once they are merged.
+pub struct Engine<F> {
+    state: State,
+    rows: usize,
+    recorded: Recorded,
+    record: F,
+}
+
+enum State {
+    Sequential(Box<Bank>),
+    Sharded(Shards),
+    Failed(Box<dyn error::Error>),
+}
+
+struct Shards {
+    owners: HashMap<u32, Option<usize>>,
+    unparsed: Vec<(usize, Outcome)>,
+    batches: Vec<Vec<Row>>,
+    senders: Vec<SyncSender<Vec<Row>>>,
+    workers: Vec<JoinHandle<Processed>>,
+}
+
+impl<F: FnMut(Outcome)> Engine<F> {
+    // Authorizations can only expire by the sequence of the whole bank, so a
+    // bank with an authorization expiry is never sharded.
+    pub fn new(bank: Bank, threads: usize, recorded: Recorded, record: F) -> Self {
+        let state = match threads > 1 && bank.authorization_expiry().is_none() {
+            true => State::Sharded(Shards::new(bank, threads, recorded)),
+            false => State::Sequential(Box::new(bank)),
+        };
+        Self {
+            state,
+            rows: 0,
+            recorded,
+            record,
+        }
+    }
+
+    pub fn process(&mut self, raw: RawRecord, transaction: Result<Transaction, ParseError>) {
+        let row = self.rows;
+        self.rows += 1;
+        if let State::Sharded(shards) = &mut self.state {
+            match transaction {
+                Err(err) => {
+                    if self.recorded.rejected {
+                        shards.unparsed.push((row, Outcome::Unparsed(err)));
+                    }
+                    return;
+                }
+                Ok(transaction) => match shards.shard_for(&transaction) {
+                    Some(shard) => {
+                        let raw = self.recorded.rejected.then_some(raw);
+                        return shards.send(shard, (row, raw, transaction));
+                    }
+                    None => {
+                        self.unshard();
+                        return self.apply(raw, Ok(transaction));
+                    }
+                },
+            }
+        }
+        self.apply(raw, transaction)
+    }
+
+    // The bank with every row applied, once every outcome has been recorded.
+    pub fn finish(mut self) -> Result<Bank, Box<dyn error::Error>> {
+        self.unshard();
+        match self.state {
+            State::Sequential(bank) => Ok(*bank),
+            State::Failed(err) => Err(err),
+            State::Sharded(_) => unreachable!("shards are merged on finishing"),
+        }
+    }
+
+    fn apply(&mut self, raw: RawRecord, transaction: Result<Transaction, ParseError>) {
+        let bank = match &mut self.state {
+            State::Sequential(bank) => bank,
+            _ => return,
+        };
+        let expired = bank.expire_authorizations();
+        let outcome = match transaction {
+            Ok(transaction) => match bank.apply_with_fees(transaction) {
+                Ok(effects) => Outcome::Applied(effects),
+                Err(rejection) => Outcome::Rejected(raw, rejection),
+            },
+            Err(err) => Outcome::Unparsed(err),
+        };
+        if !expired.is_empty() {
+            self.record(Outcome::Applied(expired));
+        }
+        self.record(outcome);
+    }
+
+    fn record(&mut self, outcome: Outcome) {
+        if self.recorded.keeps(&outcome) {
+            (self.record)(outcome);
+        }
+    }
+
+    fn unshard(&mut self) {
+        let placeholder = State::Failed("Shards are being merged".into());
+        self.state = match mem::replace(&mut self.state, placeholder) {
+            State::Sharded(shards) => match shards.join() {
+                Ok((bank, outcomes)) => {
+                    for (_, outcome) in outcomes {
+                        (self.record)(outcome);
+                    }
+                    State::Sequential(Box::new(bank))
+                }
+                Err(err) => State::Failed(err),
+            },
+            state => state,
+        };
+    }
+}
+
+impl Shards {
+    fn new(bank: Bank, threads: usize, recorded: Recorded) -> Self {
+        let shard_of = move |client: u16| client as usize % threads;
+        let owners = bank
+            .tx_owners()
+            .map(|(tx, client, to)| {
+                let shard = shard_of(client);
+                (tx, (shard == shard_of(to)).then_some(shard))
+            })
+            .collect();
+        let (senders, workers) = bank
+            .split(threads, shard_of)
+            .into_iter()
+            .map(|bank| {
+                let (sender, batches) = mpsc::sync_channel(BATCHES_IN_FLIGHT);
+                (sender, thread::spawn(move || work(bank, batches, recorded)))
+            })
+            .unzip();
+        Self {
+            owners,
+            unparsed: Vec::new(),
+            batches: (0..threads).map(|_| Vec::new()).collect(),
+            senders,
+            workers,
+        }
+    }
+    // The shard holding everything the transaction can read or change, if
+    // there is only one.
+    fn shard_for(&mut self, transaction: &Transaction) -> Option<usize> {
+        let shard_of = |client: u16| client as usize % self.senders.len();
+        let shard = shard_of(transaction.client());
+        let tx = transaction.tx();
+        match *transaction {
+            Transaction::Authorize { .. } => None,
+            Transaction::Transfer { to, .. } if shard_of(to) != shard => None,
+            Transaction::Deposit { .. }
+            | Transaction::Withdrawal { .. }
+            | Transaction::Transfer { .. }
+            | Transaction::Maintenance { .. } => {
+                let owner = *self.owners.entry(tx).or_insert(Some(shard));
+                (owner == Some(shard)).then_some(shard)
+            }
+            Transaction::Dispute { .. }
+            | Transaction::Resolve { .. }
+            | Transaction::ChargeBack { .. }
+            | Transaction::Capture { .. }
+            | Transaction::Void { .. } => match self.owners.get(&tx) {
+                Some(&owner) => owner.filter(|&owner| owner == shard),
+                None => Some(shard),
+            },
+            Transaction::Unlock { .. }
+            | Transaction::Close { .. }
+            | Transaction::Reopen { .. }
+            | Transaction::CreditLimit { .. }
+            | Transaction::Accrue { .. } => Some(shard),
+        }
+    }
+
+    fn send(&mut self, shard: usize, row: Row) {
+        self.batches[shard].push(row);
+        if self.batches[shard].len() == BATCH_SIZE {
+            // A worker that hung up has panicked, which joining reports.
+            let _ = self.senders[shard].send(mem::take(&mut self.batches[shard]));
+        }
+    }
+
+    // The merged bank and the outcomes of the shards in the order of the input.
+    fn join(self) -> Result<Processed, Box<dyn error::Error>> {
+        for (sender, batch) in self.senders.into_iter().zip(self.batches) {
+            let _ = sender.send(batch);
+        }
+        let mut banks = Vec::new();
+        let mut outcomes = self.unparsed;
+        for worker in self.workers {
+            let (bank, shard_outcomes) = worker.join().map_err(|_| "A shard worker panicked")?;
+            banks.push(bank);
+            outcomes.extend(shard_outcomes);
+        }
+        outcomes.sort_by_key(|(row, _)| *row);
+        Ok((Bank::merge(banks)?, outcomes))
+    }
+}
+
+fn work(mut bank: Bank, batches: Receiver<Vec<Row>>, recorded: Recorded) -> Processed {
+    let mut outcomes = Vec::new();
+    for (row, raw, transaction) in batches.into_iter().flatten() {
+        let outcome = match (bank.apply_with_fees(transaction), raw) {
+            (Ok(effects), _) if recorded.applied => Outcome::Applied(effects),
+            (Err(rejection), Some(raw)) => Outcome::Rejected(raw, rejection),
+            _ => continue,
+        };
+        outcomes.push((row, outcome));
+    }
+    (bank, outcomes)
+}
@@ -0,0 +1,173 @@
+use crate::{
+    amount::PrecisionPolicy,
+    bank::{policy::DisputePolicy, AppliedEffect, Bank, Rejection},
+    io::read::process_records_from_csv,
+};
+
+use super::{Engine, Outcome, Recorded, State};
+
+#[test]
+fn it_applies_rows_on_shards_with_the_same_outcome_as_on_one_bank() {
+    // Arrange
+    let mut input = String::from("type,client,tx,amount,to\n");
+    for tx in 1..=2000 {
+        let client = tx % 13 + 1;
+        let row = match tx % 10 {
+            0..=4 => format!("deposit,{client},{tx},{}.5,", tx % 97),
+            5 | 6 => format!("withdrawal,{client},{tx},{}.25,", tx % 89),
+            7 => format!("dispute,{},{},,", (tx - 7) % 13 + 1, tx - 7),
+            8 => format!("chargeback,{},{},,", (tx - 8) % 13 + 1, tx - 8),
+            _ => format!("transfer,{client},{tx},1.0,{}", client + 4),
+        };
+        input.push_str(&row);
+        input.push('\n');
+    }
+    input.push_str("deposit,1\n");
+    // Act
+    let (sharded, still_sharded) = run(&input, 4);
+    let (sequential, _) = run(&input, 1);
+    // Assert
+    assert!(still_sharded);
+    assert_eq!(sequential, sharded);
+    assert!(matches!(sharded.1.last(), Some(Outcome::Unparsed(_))));
+}
+
+#[test]
+fn it_merges_the_shards_at_a_row_needing_more_than_one_shard() {
+    // Arrange
+    let inputs = [
+        "type,client,tx,amount,to\n\
+         deposit,1,1,10.0,\n\
+         deposit,2,2,10.0,\n\
+         transfer,1,3,4.0,2\n\
+         deposit,1,4,1.0,\n",
+        "type,client,tx,amount,to\n\
+         deposit,1,1,10.0,\n\
+         deposit,2,1,10.0,\n\
+         deposit,2,2,1.0,\n",
+        "type,client,tx,amount,to\n\
+         deposit,1,1,10.0,\n\
+         dispute,2,1,,\n\
+         deposit,2,2,1.0,\n",
+        "type,client,tx,amount,to\n\
+         deposit,1,1,10.0,\n\
+         authorize,1,2,4.0,\n\
+         capture,1,2,,\n",
+    ];
+    for input in inputs {
+        // Act
+        let (sharded, still_sharded) = run(input, 2);
+        let (sequential, _) = run(input, 1);
+        // Assert
+        assert!(!still_sharded);
+        assert_eq!(sequential, sharded);
+    }
+}
+
+#[test]
+fn it_rejects_a_tx_id_reused_in_another_shard_like_one_bank_would() {
+    // Arrange
+    let input = "type,client,tx,amount\n\
+                 deposit,1,1,10.0\n\
+                 deposit,2,1,5.0\n";
+    // Act
+    let ((_, outcomes), _) = run(input, 2);
+    // Assert
+    assert!(matches!(
+        outcomes[1],
+        Outcome::Rejected(_, Rejection::DuplicateTransaction { client: 2, tx: 1 })
+    ));
+}
+
+#[test]
+fn it_applies_disputes_of_a_restored_bank_on_the_shard_holding_the_transaction() {
+    // Arrange
+    let (restored, _) = run(
+        "type,client,tx,amount\n\
+         deposit,1,1,10.0\n\
+         deposit,2,2,5.0\n\
+         withdrawal,2,3,1.0\n",
+        1,
+    );
+    let bank = || {
+        Bank::restore(restored.0.snapshot())
+            .unwrap()
+            .with_dispute_policy(DisputePolicy::PendingCredit)
+    };
+    let (mut outcomes, mut sequential_outcomes) = (Vec::new(), Vec::new());
+    let mut engine = Engine::new(bank(), 2, Recorded::ALL, |outcome| outcomes.push(outcome));
+    let mut sequential = Engine::new(bank(), 1, Recorded::ALL, |outcome| {
+        sequential_outcomes.push(outcome)
+    });
+    // Act
+    let input = "type,client,tx,amount\n\
+                 dispute,1,1,\n\
+                 dispute,2,3,\n";
+    process_records_from_csv(input.as_bytes(), &PrecisionPolicy::default(), |raw, row| {
+        engine.process(raw.clone(), row.clone());
+        sequential.process(raw, row);
+    });
+    let still_sharded = matches!(engine.state, State::Sharded(_));
+    let bank = engine.finish().unwrap();
+    let sequential_bank = sequential.finish().unwrap();
+    // Assert
+    assert!(still_sharded);
+    assert_eq!(
+        (sequential_bank, sequential_outcomes),
+        (bank, outcomes.clone())
+    );
+    assert!(matches!(
+        outcomes[1],
+        Outcome::Applied(ref effects) if matches!(effects[0], AppliedEffect::PendingRaised { client: 2, tx: 3, .. })
+    ));
+}
+
+#[test]
+fn it_only_records_the_outcomes_asked_for_in_the_order_of_the_input() {
+    // Arrange
+    let input = "type,client,tx,amount\n\
+                 deposit,1,1,10.0\n\
+                 withdrawal,2,2,5.0\n\
+                 deposit,2\n\
+                 withdrawal,1,3,50.0\n\
+                 deposit,2,4,1.0\n";
+    let rejected = Recorded {
+        applied: false,
+        rejected: true,
+    };
+    for threads in [1, 2] {
+        let mut outcomes = Vec::new();
+        let mut engine = Engine::new(Bank::default(), threads, rejected, |outcome| {
+            outcomes.push(outcome)
+        });
+        // Act
+        process_records_from_csv(input.as_bytes(), &PrecisionPolicy::default(), |raw, row| {
+            engine.process(raw, row)
+        });
+        engine.finish().unwrap();
+        // Assert
+        assert_eq!(3, outcomes.len());
+        assert!(matches!(
+            outcomes[0],
+            Outcome::Rejected(_, Rejection::UnknownClient { client: 2 })
+        ));
+        assert!(matches!(outcomes[1], Outcome::Unparsed(_)));
+        assert!(matches!(
+            outcomes[2],
+            Outcome::Rejected(_, Rejection::InsufficientFunds { client: 1, tx: 3 })
+        ));
+    }
+}
+
+fn run(input: &str, threads: usize) -> ((Bank, Vec<Outcome>), bool) {
+    let mut outcomes = Vec::new();
+    let mut engine = Engine::new(Bank::default(), threads, Recorded::ALL, |outcome| {
+        outcomes.push(outcome)
+    });
+    process_records_from_csv(input.as_bytes(), &PrecisionPolicy::default(), |raw, row| {
+        engine.process(raw, row)
+    });
+    let sharded = matches!(engine.state, State::Sharded(_));
+    let bank = engine.finish().unwrap();
+    ((bank, outcomes), sharded)
+}
@@ -1,2 +1,4 @@
 pub mod read;
 pub mod write;
+
+pub const SNAPSHOT_VERSION: u32 = 14;
@@ -1,10 +1,19 @@
-use std::{error, io};
+use std::{error, fmt, io};
 
-use bigdecimal::BigDecimal;
-use csv::Trim;
+use csv::{StringRecord, Trim};
 use serde::Deserialize;
 
-use crate::bank::Transaction;
+use crate::{
+    amount::{Amount, AmountError, PrecisionPolicy},
+    bank::{
+        ledger::LedgerBalance,
+        policy::{FeeSchedule, InterestSchedule, RateTier},
+        Account, AccountStatus, Accrual, AppliedEffect, Authorization, ClosedAuthorization,
+        DisputeState, Fee, HistoryEntry, Snapshot, Transaction,
+    },
+    date::Date,
+    io::SNAPSHOT_VERSION,
+};
 
 #[cfg(test)]
 mod tests;
@@ -13,18 +22,353 @@ pub fn processs_transactions_from_csv<ProcessCallback>(
     reader: impl io::Read,
     mut process: ProcessCallback,
 ) where
-    ProcessCallback: FnMut(Result<Transaction, Box<dyn error::Error>>),
+    ProcessCallback: FnMut(Result<Transaction, ParseError>),
 {
-    for record in csv::ReaderBuilder::new()
+    process_records_from_csv(reader, &PrecisionPolicy::default(), |_, transaction| {
+        process(transaction)
+    });
+}
+
+pub fn process_records_from_csv<ProcessCallback>(
+    reader: impl io::Read,
+    precision: &PrecisionPolicy,
+    mut process: ProcessCallback,
+) where
+    ProcessCallback: FnMut(RawRecord, Result<Transaction, ParseError>),
+{
+    let mut reader = csv::ReaderBuilder::new()
         .flexible(true)
+        .trim(Trim::All)
+        .from_reader(reader);
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(err) => {
+            let err = ParseError::from_csv(err);
+            return process(err.raw().clone(), Err(err));
+        }
+    };
+    for record in reader.records() {
+        match record {
+            Ok(record) => {
+                let raw = RawRecord::from(&record);
+                let transaction = try_transaction_from(&headers, &record, &raw, precision);
+                process(raw, transaction);
+            }
+            Err(err) => {
+                let err = ParseError::from_csv(err);
+                process(err.raw().clone(), Err(err));
+            }
+        }
+    }
+}
+
+pub fn read_journal_from_csv(
+    reader: impl io::Read,
+) -> Result<Vec<AppliedEffect>, Box<dyn error::Error>> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(reader)
+        .deserialize::<JournalRecord>()
+        .map(|record| AppliedEffect::try_from(record?))
+        .collect()
+}
+
+pub fn read_fee_schedule_from_csv(
+    reader: impl io::Read,
+) -> Result<FeeSchedule, Box<dyn error::Error>> {
+    let mut schedule = FeeSchedule::default();
+    for record in csv::ReaderBuilder::new()
+        .trim(Trim::All)
+        .from_reader(reader)
+        .deserialize::<FeeRecord>()
+    {
+        let record = record?;
+        let flat = parse_rule_amount(&record.flat)?;
+        let percent = parse_rule_amount(&record.percent)?;
+        match record.fee.as_str() {
+            "withdrawal" => {
+                schedule.withdrawal_flat = flat;
+                schedule.withdrawal_percent = percent;
+            }
+            "chargeback" if percent == Amount::ZERO => schedule.chargeback = flat,
+            "maintenance" if percent == Amount::ZERO => schedule.maintenance = flat,
+            "chargeback" | "maintenance" => {
+                return Err(
+                    format!("Only withdrawal fees can be a percentage: {}", record.fee).into(),
+                )
+            }
+            fee => return Err(format!("Invalid fee: {fee}").into()),
+        }
+    }
+    Ok(schedule)
+}
+
+pub fn read_interest_schedule_from_csv(
+    reader: impl io::Read,
+) -> Result<InterestSchedule, Box<dyn error::Error>> {
+    let mut schedule = InterestSchedule::default();
+    for record in csv::ReaderBuilder::new()
         .trim(Trim::All)
         .from_reader(reader)
-        .deserialize::<TransactionRecord>()
+        .deserialize::<InterestRecord>()
     {
-        process(match record {
-            Ok(rec) => Transaction::try_from(rec),
-            Err(err) => Err(err.into()),
-        });
+        let record = record?;
+        let tier = RateTier {
+            from: parse_rule_amount(&record.from)?,
+            rate: parse_rule_amount(&record.rate)?,
+        };
+        match record.kind.as_str() {
+            "credit" => schedule.credit.push(tier),
+            "debit" => schedule.debit.push(tier),
+            kind => return Err(format!("Invalid interest rate kind: {kind}").into()),
+        }
+    }
+    Ok(schedule)
+}
+
+fn parse_rule_amount(amount: &Option<String>) -> Result<Amount, Box<dyn error::Error>> {
+    match amount.as_deref() {
+        None | Some("") => Ok(Amount::ZERO),
+        Some(amount) => match amount.parse::<Amount>()? {
+            amount if amount.is_negative() => {
+                Err(format!("Negative amount in rules: {amount}").into())
+            }
+            amount => Ok(amount),
+        },
+    }
+}
+
+pub fn read_snapshot_from_csv(reader: impl io::Read) -> Result<Snapshot, Box<dyn error::Error>> {
+    let mut records = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(reader)
+        .into_records();
+    let version = match records.next() {
+        Some(record) => record?.deserialize::<(String, u32)>(None)?,
+        None => return Err("Empty snapshot".into()),
+    };
+    let version = match version {
+        (kind, version @ 1..=SNAPSHOT_VERSION) if kind == "snapshot" => version,
+        (kind, version) => {
+            return Err(format!("Unsupported snapshot version: {kind} {version}").into())
+        }
+    };
+    let mut snapshot = Snapshot::default();
+    for record in records {
+        let record = record?;
+        match record.get(0) {
+            Some("account") => {
+                let mut record = record;
+                if version < 3 {
+                    record.push_field("0");
+                }
+                if version < 4 {
+                    record.push_field("false");
+                    record.push_field("0");
+                }
+                if version < 8 {
+                    record.push_field("0");
+                }
+                if version < 10 {
+                    record.push_field("0");
+                }
+                let (
+                    _,
+                    client,
+                    available,
+                    held,
+                    status,
+                    pending_credit,
+                    flagged,
+                    receivable,
+                    credit_limit,
+                    authorized,
+                ) = record.deserialize::<AccountRow>(None)?;
+                snapshot.accounts.push(Account {
+                    client,
+                    available: available.parse()?,
+                    held: held.parse()?,
+                    status: parse_account_status(version, &status)?,
+                    pending_credit: pending_credit.parse()?,
+                    flagged,
+                    receivable: receivable.parse()?,
+                    credit_limit: credit_limit.parse()?,
+                    authorized: authorized.parse()?,
+                });
+            }
+            Some("deposit") => {
+                let (client, tx, amount, state, disputed, charged_back) =
+                    parse_stored_transaction(version, record)?;
+                snapshot.transactions.push(Transaction::Deposit {
+                    client,
+                    tx,
+                    amount,
+                    state,
+                    disputed,
+                    charged_back,
+                });
+            }
+            Some("withdrawal") => {
+                let (client, tx, amount, state, disputed, charged_back) =
+                    parse_stored_transaction(version, record)?;
+                snapshot.transactions.push(Transaction::Withdrawal {
+                    client,
+                    tx,
+                    amount,
+                    state,
+                    disputed,
+                    charged_back,
+                });
+            }
+            Some("transfer") => {
+                let to = record.get(7).unwrap_or_default().parse()?;
+                let (client, tx, amount, state, disputed, charged_back) =
+                    parse_stored_transaction(version, record)?;
+                snapshot.transactions.push(Transaction::Transfer {
+                    client,
+                    to,
+                    tx,
+                    amount,
+                    state,
+                    disputed,
+                    charged_back,
+                });
+            }
+            Some("fee") => {
+                let (_, client, tx, amount) =
+                    record.deserialize::<(String, u16, u32, String)>(None)?;
+                snapshot.fees.push(Fee {
+                    client,
+                    tx,
+                    amount: amount.parse()?,
+                });
+            }
+            Some("authorization") => {
+                let (_, client, tx, amount, authorized_at) =
+                    record.deserialize::<(String, u16, u32, String, u64)>(None)?;
+                snapshot.authorizations.push(Authorization {
+                    client,
+                    tx,
+                    amount: amount.parse()?,
+                    authorized_at,
+                });
+            }
+            Some("closed-authorization") => {
+                let (_, client, tx) = record.deserialize::<(String, u16, u32)>(None)?;
+                snapshot
+                    .closed_authorizations
+                    .push(ClosedAuthorization { client, tx });
+            }
+            Some("sequence") => {
+                let (_, sequence) = record.deserialize::<(String, u64)>(None)?;
+                snapshot.sequence = sequence;
+            }
+            Some("ledger") => {
+                let (_, account, balance) = record.deserialize::<(String, String, String)>(None)?;
+                snapshot.ledger.push(LedgerBalance {
+                    account: account.parse()?,
+                    balance: balance.parse()?,
+                });
+            }
+            Some("history") => {
+                let (_, client, available, held, total) =
+                    record.deserialize::<(String, u16, String, String, String)>(None)?;
+                let effect = StringRecord::from_iter(record.iter().skip(5))
+                    .deserialize::<JournalRecord>(None)?;
+                snapshot.history.push(HistoryEntry {
+                    client,
+                    effect: effect.try_into()?,
+                    available: available.parse()?,
+                    held: held.parse()?,
+                    total: total.parse()?,
+                });
+            }
+            Some("accrual") => {
+                let (_, client, as_of) = record.deserialize::<(String, u16, String)>(None)?;
+                snapshot.accruals.push(Accrual {
+                    client,
+                    as_of: as_of.parse()?,
+                });
+            }
+            _ => return Err(format!("Invalid snapshot record: {record:?}").into()),
+        }
+    }
+    Ok(snapshot)
+}
+
+type AccountRow = (
+    String,
+    u16,
+    String,
+    String,
+    String,
+    String,
+    bool,
+    String,
+    String,
+    String,
+);
+
+// Snapshots before version 6 only recorded whether an account was locked.
+fn parse_account_status(
+    version: u32,
+    status: &str,
+) -> Result<AccountStatus, Box<dyn error::Error>> {
+    match (version, status) {
+        (..=5, "true") => Ok(AccountStatus::Locked),
+        (..=5, "false") => Ok(AccountStatus::Active),
+        (_, status) => Ok(status.parse()?),
+    }
+}
+
+type StoredTransaction = (u16, u32, Amount, DisputeState, Amount, Amount);
+
+// Snapshots before version 5 only held whole disputes and chargebacks.
+fn parse_stored_transaction(
+    version: u32,
+    mut record: StringRecord,
+) -> Result<StoredTransaction, Box<dyn error::Error>> {
+    if version < 5 {
+        record.push_field("");
+        record.push_field("");
+    }
+    let (_, client, tx, amount, state, disputed, charged_back) =
+        record.deserialize::<(String, u16, u32, String, String, String, String)>(None)?;
+    let amount = amount.parse::<Amount>()?;
+    let state = parse_dispute_state(version, &state)?;
+    let (disputed, charged_back) = match version {
+        5.. => (disputed.parse()?, charged_back.parse()?),
+        _ => match state {
+            DisputeState::Disputed => (amount, Amount::ZERO),
+            DisputeState::ChargedBack => (Amount::ZERO, amount),
+            _ => (Amount::ZERO, Amount::ZERO),
+        },
+    };
+    Ok((client, tx, amount, state, disputed, charged_back))
+}
+
+// Version 1 snapshots only recorded whether a transaction was under dispute.
+fn parse_dispute_state(version: u32, state: &str) -> Result<DisputeState, Box<dyn error::Error>> {
+    match (version, state) {
+        (1, "true") => Ok(DisputeState::Disputed),
+        (1, "false") => Ok(DisputeState::Settled),
+        (_, state) => Ok(state.parse()?),
+    }
+}
+
+fn try_transaction_from(
+    headers: &StringRecord,
+    record: &StringRecord,
+    raw: &RawRecord,
+    precision: &PrecisionPolicy,
+) -> Result<Transaction, ParseError> {
+    match record.deserialize::<TransactionRecord>(Some(headers)) {
+        Ok(rec) => rec
+            .into_transaction(precision)
+            .map_err(|err| err.with_raw(raw.clone())),
+        Err(err) => Err(ParseError::Csv(raw.clone(), err.to_string())),
     }
 }
 
@@ -35,22 +379,240 @@ pub struct TransactionRecord {
     client: u16,
     tx: u32,
     amount: Option<String>,
+    #[serde(default)]
+    to: Option<u16>,
 }
 
-impl TryFrom<TransactionRecord> for Transaction {
+#[derive(Clone, Debug, Deserialize)]
+pub struct FeeRecord {
+    fee: String,
+    flat: Option<String>,
+    percent: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct InterestRecord {
+    kind: String,
+    from: Option<String>,
+    rate: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct JournalRecord {
+    effect: String,
+    client: u16,
+    tx: u32,
+    amount: String,
+    // The other client of a transfer, or the date interest was accrued to.
+    #[serde(default)]
+    detail: Option<String>,
+}
+
+impl TryFrom<JournalRecord> for AppliedEffect {
     type Error = Box<dyn error::Error>;
 
+    fn try_from(rec: JournalRecord) -> Result<Self, Self::Error> {
+        let (client, tx, amount) = (rec.client, rec.tx, rec.amount.parse::<Amount>()?);
+        match rec.effect.as_str() {
+            "credited" => Ok(AppliedEffect::Credited { client, tx, amount }),
+            "debited" => Ok(AppliedEffect::Debited { client, tx, amount }),
+            "transferred" => Ok(AppliedEffect::Transferred {
+                client,
+                to: rec.detail.ok_or("Missing transfer destination")?.parse()?,
+                tx,
+                amount,
+            }),
+            "held" => Ok(AppliedEffect::Held { client, tx, amount }),
+            "held_flagged" => Ok(AppliedEffect::HeldFlagged { client, tx, amount }),
+            "held_with_receivable" => Ok(AppliedEffect::HeldWithReceivable { client, tx, amount }),
+            "released" => Ok(AppliedEffect::Released { client, tx, amount }),
+            "reversed" => Ok(AppliedEffect::Reversed { client, tx, amount }),
+            "returned" => Ok(AppliedEffect::Returned {
+                client,
+                to: rec.detail.ok_or("Missing transfer sender")?.parse()?,
+                tx,
+                amount,
+            }),
+            "pending_raised" => Ok(AppliedEffect::PendingRaised { client, tx, amount }),
+            "pending_dropped" => Ok(AppliedEffect::PendingDropped { client, tx, amount }),
+            "pending_granted" => Ok(AppliedEffect::PendingGranted { client, tx, amount }),
+            "unlocked" => Ok(AppliedEffect::Unlocked { client, tx }),
+            "closed" => Ok(AppliedEffect::Closed { client, tx }),
+            "reopened" => Ok(AppliedEffect::Reopened { client, tx }),
+            "credit_limit_set" => Ok(AppliedEffect::CreditLimitSet { client, tx, amount }),
+            "fee_charged" => Ok(AppliedEffect::FeeCharged { client, tx, amount }),
+            "fee_refunded" => Ok(AppliedEffect::FeeRefunded { client, tx, amount }),
+            "authorized" => Ok(AppliedEffect::Authorized { client, tx, amount }),
+            "captured" => Ok(AppliedEffect::Captured { client, tx, amount }),
+            "voided" => Ok(AppliedEffect::Voided { client, tx, amount }),
+            "expired" => Ok(AppliedEffect::Expired { client, tx, amount }),
+            "interest_posted" => Ok(AppliedEffect::InterestPosted {
+                client,
+                tx,
+                amount,
+                as_of: rec.detail.ok_or("Missing accrual date")?.parse()?,
+            }),
+            effect => Err(format!("Invalid journal effect: {effect}").into()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RawRecord {
+    pub line: u64,
+    pub byte: u64,
+    pub fields: Vec<String>,
+}
+
+impl From<&StringRecord> for RawRecord {
+    fn from(record: &StringRecord) -> Self {
+        let (line, byte) = record
+            .position()
+            .map_or((0, 0), |pos| (pos.line(), pos.byte()));
+        Self {
+            line,
+            byte,
+            fields: record.iter().map(String::from).collect(),
+        }
+    }
+}
+
+// Why a row could not be read, with its position and raw fields. Negative
+// amounts are reported as NonPositiveAmount along with zero ones, and rows the
+// CSV reader itself rejects as Csv.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseError {
+    MissingAmount(RawRecord),
+    MissingDestination(RawRecord),
+    InvalidAmount(RawRecord),
+    InvalidDate(RawRecord),
+    ExcessPrecision(RawRecord),
+    NonPositiveAmount(RawRecord),
+    UnknownType(RawRecord),
+    Csv(RawRecord, String),
+}
+
+impl ParseError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::MissingAmount(_) => "missing_amount",
+            ParseError::MissingDestination(_) => "missing_destination",
+            ParseError::InvalidAmount(_) => "invalid_amount",
+            ParseError::InvalidDate(_) => "invalid_date",
+            ParseError::ExcessPrecision(_) => "excess_precision",
+            ParseError::NonPositiveAmount(_) => "non_positive_amount",
+            ParseError::UnknownType(_) => "unknown_type",
+            ParseError::Csv(..) => "malformed_record",
+        }
+    }
+
+    pub fn raw(&self) -> &RawRecord {
+        match self {
+            ParseError::MissingAmount(raw)
+            | ParseError::MissingDestination(raw)
+            | ParseError::InvalidAmount(raw)
+            | ParseError::InvalidDate(raw)
+            | ParseError::ExcessPrecision(raw)
+            | ParseError::NonPositiveAmount(raw)
+            | ParseError::UnknownType(raw)
+            | ParseError::Csv(raw, _) => raw,
+        }
+    }
+
+    fn with_raw(self, raw: RawRecord) -> Self {
+        match self {
+            ParseError::MissingAmount(_) => ParseError::MissingAmount(raw),
+            ParseError::MissingDestination(_) => ParseError::MissingDestination(raw),
+            ParseError::InvalidAmount(_) => ParseError::InvalidAmount(raw),
+            ParseError::InvalidDate(_) => ParseError::InvalidDate(raw),
+            ParseError::ExcessPrecision(_) => ParseError::ExcessPrecision(raw),
+            ParseError::NonPositiveAmount(_) => ParseError::NonPositiveAmount(raw),
+            ParseError::UnknownType(_) => ParseError::UnknownType(raw),
+            ParseError::Csv(_, message) => ParseError::Csv(raw, message),
+        }
+    }
+
+    fn from_csv(err: csv::Error) -> Self {
+        let (line, byte) = err
+            .position()
+            .map_or((0, 0), |pos| (pos.line(), pos.byte()));
+        ParseError::Csv(
+            RawRecord {
+                line,
+                byte,
+                fields: Vec::new(),
+            },
+            err.to_string(),
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let raw = self.raw();
+        write!(f, "line {} (byte {}): ", raw.line, raw.byte)?;
+        match self {
+            ParseError::MissingAmount(_) => write!(f, "missing amount"),
+            ParseError::MissingDestination(_) => write!(f, "missing transfer destination"),
+            ParseError::InvalidAmount(_) => write!(f, "invalid amount"),
+            ParseError::InvalidDate(_) => write!(f, "missing or invalid date"),
+            ParseError::ExcessPrecision(_) => write!(f, "amount has too many decimal places"),
+            ParseError::NonPositiveAmount(_) => write!(f, "amount is not positive"),
+            ParseError::UnknownType(_) => write!(f, "unknown transaction type"),
+            ParseError::Csv(_, message) => write!(f, "{message}"),
+        }?;
+        write!(f, " in record [{}]", raw.fields.join(","))
+    }
+}
+
+impl error::Error for ParseError {}
+
+impl TryFrom<TransactionRecord> for Transaction {
+    type Error = ParseError;
+
     fn try_from(rec: TransactionRecord) -> Result<Self, Self::Error> {
-        match rec.trans_type.as_str() {
-            "deposit" => try_deposit_from(rec.client, rec.tx, rec.amount),
-            "withdrawal" => try_withdrawal_from(rec.client, rec.tx, rec.amount),
-            "dispute" => try_dispute_from(rec.client, rec.tx),
-            "resolve" => try_resolve_from(rec.client, rec.tx),
-            "chargeback" => try_chargeback_from(rec.client, rec.tx),
-            _ => Err(Box::new(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Invalid transaction type",
-            ))),
+        rec.into_transaction(&PrecisionPolicy::default())
+    }
+}
+
+impl TransactionRecord {
+    pub fn into_transaction(self, precision: &PrecisionPolicy) -> Result<Transaction, ParseError> {
+        match self.trans_type.as_str() {
+            "deposit" => try_deposit_from(self.client, self.tx, self.amount, precision),
+            "withdrawal" => try_withdrawal_from(self.client, self.tx, self.amount, precision),
+            "transfer" => try_transfer_from(self.client, self.to, self.tx, self.amount, precision),
+            "dispute" => try_dispute_from(self.client, self.tx, self.amount, precision),
+            "resolve" => try_resolve_from(self.client, self.tx, self.amount, precision),
+            "chargeback" => try_chargeback_from(self.client, self.tx, self.amount, precision),
+            "unlock" => Ok(Transaction::Unlock {
+                client: self.client,
+                tx: self.tx,
+            }),
+            "close" => Ok(Transaction::Close {
+                client: self.client,
+                tx: self.tx,
+            }),
+            "reopen" => Ok(Transaction::Reopen {
+                client: self.client,
+                tx: self.tx,
+            }),
+            "limit" => try_credit_limit_from(self.client, self.tx, self.amount, precision),
+            "maintenance" => Ok(Transaction::Maintenance {
+                client: self.client,
+                tx: self.tx,
+            }),
+            "authorize" => try_authorize_from(self.client, self.tx, self.amount, precision),
+            "capture" => try_capture_from(self.client, self.tx, self.amount, precision),
+            "void" => Ok(Transaction::Void {
+                client: self.client,
+                tx: self.tx,
+            }),
+            "accrue" => Ok(Transaction::Accrue {
+                client: self.client,
+                tx: self.tx,
+                as_of: parse_date(self.amount)?,
+            }),
+            _ => Err(ParseError::UnknownType(RawRecord::default())),
         }
     }
 }
@@ -59,12 +621,15 @@ fn try_deposit_from(
     client: u16,
     tx: u32,
     amount: Option<String>,
-) -> Result<Transaction, Box<dyn error::Error>> {
+    precision: &PrecisionPolicy,
+) -> Result<Transaction, ParseError> {
     Ok(Transaction::Deposit {
         client,
         tx,
-        amount: parse_amount(amount)?,
-        disputed: false,
+        amount: parse_amount(amount, precision)?,
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
     })
 }
 
@@ -72,39 +637,148 @@ fn try_withdrawal_from(
     client: u16,
     tx: u32,
     amount: Option<String>,
-) -> Result<Transaction, Box<dyn error::Error>> {
+    precision: &PrecisionPolicy,
+) -> Result<Transaction, ParseError> {
     Ok(Transaction::Withdrawal {
         client,
         tx,
-        amount: parse_amount(amount)?,
-        disputed: false,
+        amount: parse_amount(amount, precision)?,
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
     })
 }
 
-fn try_dispute_from(client: u16, tx: u32) -> Result<Transaction, Box<dyn error::Error>> {
+fn try_transfer_from(
+    client: u16,
+    to: Option<u16>,
+    tx: u32,
+    amount: Option<String>,
+    precision: &PrecisionPolicy,
+) -> Result<Transaction, ParseError> {
+    Ok(Transaction::Transfer {
+        client,
+        to: to.ok_or(ParseError::MissingDestination(RawRecord::default()))?,
+        tx,
+        amount: parse_amount(amount, precision)?,
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })
+}
+
+fn try_credit_limit_from(
+    client: u16,
+    tx: u32,
+    amount: Option<String>,
+    precision: &PrecisionPolicy,
+) -> Result<Transaction, ParseError> {
+    Ok(Transaction::CreditLimit {
+        client,
+        tx,
+        amount: parse_signed_amount(amount, precision)?,
+    })
+}
+
+fn try_authorize_from(
+    client: u16,
+    tx: u32,
+    amount: Option<String>,
+    precision: &PrecisionPolicy,
+) -> Result<Transaction, ParseError> {
+    Ok(Transaction::Authorize {
+        client,
+        tx,
+        amount: parse_amount(amount, precision)?,
+    })
+}
+
+fn try_capture_from(
+    client: u16,
+    tx: u32,
+    amount: Option<String>,
+    precision: &PrecisionPolicy,
+) -> Result<Transaction, ParseError> {
+    Ok(Transaction::Capture {
+        client,
+        tx,
+        amount: parse_partial_amount(amount, precision)?,
+    })
+}
+
+fn try_dispute_from(
+    client: u16,
+    tx: u32,
+    amount: Option<String>,
+    precision: &PrecisionPolicy,
+) -> Result<Transaction, ParseError> {
     Ok(Transaction::Dispute {
         client,
         referenced_tx: tx,
+        amount: parse_partial_amount(amount, precision)?,
     })
 }
 
-fn try_resolve_from(client: u16, tx: u32) -> Result<Transaction, Box<dyn error::Error>> {
+fn try_resolve_from(
+    client: u16,
+    tx: u32,
+    amount: Option<String>,
+    precision: &PrecisionPolicy,
+) -> Result<Transaction, ParseError> {
     Ok(Transaction::Resolve {
         client,
         referenced_tx: tx,
+        amount: parse_partial_amount(amount, precision)?,
     })
 }
 
-fn try_chargeback_from(client: u16, tx: u32) -> Result<Transaction, Box<dyn error::Error>> {
+fn try_chargeback_from(
+    client: u16,
+    tx: u32,
+    amount: Option<String>,
+    precision: &PrecisionPolicy,
+) -> Result<Transaction, ParseError> {
     Ok(Transaction::ChargeBack {
         client,
         referenced_tx: tx,
+        amount: parse_partial_amount(amount, precision)?,
     })
 }
 
-fn parse_amount(amount: Option<String>) -> Result<BigDecimal, bigdecimal::ParseBigDecimalError> {
+fn parse_date(date: Option<String>) -> Result<Date, ParseError> {
+    date.and_then(|date| date.parse().ok())
+        .ok_or(ParseError::InvalidDate(RawRecord::default()))
+}
+
+fn parse_partial_amount(
+    amount: Option<String>,
+    precision: &PrecisionPolicy,
+) -> Result<Option<Amount>, ParseError> {
+    match amount {
+        Some(amount) if !amount.is_empty() => parse_amount(Some(amount), precision).map(Some),
+        _ => Ok(None),
+    }
+}
+
+fn parse_amount(amount: Option<String>, precision: &PrecisionPolicy) -> Result<Amount, ParseError> {
+    match parse_signed_amount(amount, precision)? {
+        amount if amount > Amount::ZERO => Ok(amount),
+        _ => Err(ParseError::NonPositiveAmount(RawRecord::default())),
+    }
+}
+
+fn parse_signed_amount(
+    amount: Option<String>,
+    precision: &PrecisionPolicy,
+) -> Result<Amount, ParseError> {
     match amount {
-        Some(amount) => amount.parse::<BigDecimal>(),
-        _ => todo!(),
+        Some(amount) => match precision.parse(&amount) {
+            Ok(amount) => Ok(amount),
+            Err(AmountError::ExcessPrecision) => {
+                Err(ParseError::ExcessPrecision(RawRecord::default()))
+            }
+            Err(_) => Err(ParseError::InvalidAmount(RawRecord::default())),
+        },
+        None => Err(ParseError::MissingAmount(RawRecord::default())),
     }
 }
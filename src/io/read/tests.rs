@@ -1,8 +1,19 @@
 use std::str::FromStr;
 
-use bigdecimal::BigDecimal;
+use crate::amount::Amount;
 
-use crate::bank::Transaction;
+use crate::{
+    amount::{ExcessPrecision, PrecisionPolicy, RoundingMode},
+    bank::{
+        ledger::{LedgerAccount, LedgerBalance},
+        policy::{FeeSchedule, RateTier},
+        Account, AccountStatus, Accrual, AppliedEffect, Authorization, Bank, DisputeState,
+        HistoryEntry, Snapshot, Transaction,
+    },
+    date::Date,
+};
+
+use super::{ParseError, RawRecord};
 
 #[test]
 fn it_reads_a_properly_formatted_csv_with_all_transaction_types() {
@@ -24,32 +35,42 @@ fn it_reads_a_properly_formatted_csv_with_all_transaction_types() {
         Transaction::Deposit {
             client: 1,
             tx: 1,
-            amount: BigDecimal::from_str("1.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("1.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Deposit {
             client: 2,
             tx: 2,
-            amount: BigDecimal::from_str("2.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("2.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Deposit {
             client: 1,
             tx: 3,
-            amount: BigDecimal::from_str("2.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("2.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Withdrawal {
             client: 1,
             tx: 4,
-            amount: BigDecimal::from_str("1.5").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("1.5").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Withdrawal {
             client: 2,
             tx: 5,
-            amount: BigDecimal::from_str("3.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("3.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
     ];
     // Act
@@ -82,26 +103,34 @@ fn it_reads_a_csv_file_containing_an_invalid_amount_but_processes_all_other_tran
         Transaction::Deposit {
             client: 1,
             tx: 1,
-            amount: BigDecimal::from_str("1.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("1.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Deposit {
             client: 2,
             tx: 2,
-            amount: BigDecimal::from_str("2.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("2.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Withdrawal {
             client: 1,
             tx: 4,
-            amount: BigDecimal::from_str("1.5").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("1.5").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Withdrawal {
             client: 2,
             tx: 5,
-            amount: BigDecimal::from_str("3.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("3.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
     ];
     // Act
@@ -138,26 +167,34 @@ fn it_reads_a_csv_file_containing_an_invalid_transaction_type_but_processes_all_
         Transaction::Deposit {
             client: 1,
             tx: 1,
-            amount: BigDecimal::from_str("1.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("1.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Deposit {
             client: 2,
             tx: 2,
-            amount: BigDecimal::from_str("2.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("2.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Withdrawal {
             client: 1,
             tx: 4,
-            amount: BigDecimal::from_str("1.5").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("1.5").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Withdrawal {
             client: 2,
             tx: 5,
-            amount: BigDecimal::from_str("3.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("3.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
     ];
     // Act
@@ -202,32 +239,42 @@ fn it_reads_a_csv_file_containing_malformed_transactions_of_various_kinds_but_pr
         Transaction::Deposit {
             client: 1,
             tx: 1,
-            amount: BigDecimal::from_str("1.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("1.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Deposit {
             client: 2,
             tx: 2,
-            amount: BigDecimal::from_str("2.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("2.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Deposit {
             client: 1,
             tx: 3,
-            amount: BigDecimal::from_str("2.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("2.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Withdrawal {
             client: 1,
             tx: 4,
-            amount: BigDecimal::from_str("1.5").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("1.5").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
         Transaction::Withdrawal {
             client: 2,
             tx: 5,
-            amount: BigDecimal::from_str("3.0").unwrap(),
-            disputed: false,
+            amount: Amount::from_str("3.0").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
         },
     ];
     // Act
@@ -242,3 +289,768 @@ fn it_reads_a_csv_file_containing_malformed_transactions_of_various_kinds_but_pr
         assert_eq!(expected, actual);
     }
 }
+
+#[test]
+fn it_reports_malformed_rows_as_parse_errors_with_their_position_and_raw_record() {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "type,client,tx,amount\n\
+              deposit,1,1\n\
+              deposit,1,2,2.x\n\
+              deposits,1,3,2.0\n\
+              deposit,1.1,4,1.0\n\
+              withdrawal,1,5,\n\
+             ",
+    );
+    let expected = [
+        ParseError::MissingAmount(RawRecord {
+            line: 2,
+            byte: 22,
+            fields: vec!["deposit".into(), "1".into(), "1".into()],
+        }),
+        ParseError::InvalidAmount(RawRecord {
+            line: 3,
+            byte: 34,
+            fields: vec!["deposit".into(), "1".into(), "2".into(), "2.x".into()],
+        }),
+        ParseError::UnknownType(RawRecord {
+            line: 4,
+            byte: 50,
+            fields: vec!["deposits".into(), "1".into(), "3".into(), "2.0".into()],
+        }),
+    ];
+    // Act
+    let mut actual = Vec::new();
+    super::processs_transactions_from_csv(input, |trans| actual.push(trans.unwrap_err()));
+    // Assert
+    assert_eq!(5, actual.len());
+    assert_eq!(expected[..], actual[..3]);
+    assert!(matches!(&actual[3], ParseError::Csv(raw, _) if raw.line == 5));
+    assert!(matches!(&actual[4], ParseError::MissingAmount(raw) if raw.line == 6));
+}
+
+#[test]
+fn it_reads_a_journal_of_applied_effects() -> Result<(), Box<dyn std::error::Error>> {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "credited,1,1,1.0\n\
+         debited,1,2,0.5\n\
+         held,1,2,-0.5\n\
+         released,1,2,-0.5\n\
+         held,1,1,1.0\n\
+         reversed,1,1,1.0\n\
+         transferred,1,3,0.25,2\n\
+         returned,2,3,0.25,1\n\
+         interest_posted,1,0,0.0123,2024-01-31\n\
+        ",
+    );
+    let amount = |amount| Amount::from_str(amount).unwrap();
+    let expected = vec![
+        AppliedEffect::Credited {
+            client: 1,
+            tx: 1,
+            amount: amount("1.0"),
+        },
+        AppliedEffect::Debited {
+            client: 1,
+            tx: 2,
+            amount: amount("0.5"),
+        },
+        AppliedEffect::Held {
+            client: 1,
+            tx: 2,
+            amount: amount("-0.5"),
+        },
+        AppliedEffect::Released {
+            client: 1,
+            tx: 2,
+            amount: amount("-0.5"),
+        },
+        AppliedEffect::Held {
+            client: 1,
+            tx: 1,
+            amount: amount("1.0"),
+        },
+        AppliedEffect::Reversed {
+            client: 1,
+            tx: 1,
+            amount: amount("1.0"),
+        },
+        AppliedEffect::Transferred {
+            client: 1,
+            to: 2,
+            tx: 3,
+            amount: amount("0.25"),
+        },
+        AppliedEffect::Returned {
+            client: 2,
+            to: 1,
+            tx: 3,
+            amount: amount("0.25"),
+        },
+        AppliedEffect::InterestPosted {
+            client: 1,
+            tx: 0,
+            amount: amount("0.0123"),
+            as_of: Date::from_str("2024-01-31").unwrap(),
+        },
+    ];
+    // Act
+    let actual = super::read_journal_from_csv(input)?;
+    // Assert
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_read_a_journal_with_an_unknown_effect() {
+    // Arrange
+    let input = stringreader::StringReader::new("credited,1,1,1.0\nfrozen,1,1,1.0\n");
+    // Act
+    let actual = super::read_journal_from_csv(input);
+    // Assert
+    assert!(actual.is_err());
+}
+
+#[test]
+fn it_reads_a_version_1_snapshot_with_disputed_flags_as_dispute_states(
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "snapshot,1\n\
+         account,1,5.0,10.0,false\n\
+         deposit,1,1,20.0,false\n\
+         withdrawal,1,2,10.0,true\n\
+        ",
+    );
+    let expected = Snapshot {
+        accounts: vec![Account {
+            client: 1,
+            available: Amount::from_str("5.0").unwrap(),
+            held: Amount::from_str("10.0").unwrap(),
+            status: AccountStatus::Active,
+            pending_credit: Amount::ZERO,
+            flagged: false,
+            receivable: Amount::ZERO,
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        }],
+        transactions: vec![
+            Transaction::Deposit {
+                client: 1,
+                tx: 1,
+                amount: Amount::from_str("20.0").unwrap(),
+                state: DisputeState::Settled,
+                disputed: Amount::ZERO,
+                charged_back: Amount::ZERO,
+            },
+            Transaction::Withdrawal {
+                client: 1,
+                tx: 2,
+                amount: Amount::from_str("10.0").unwrap(),
+                state: DisputeState::Disputed,
+                disputed: Amount::from_str("10.0").unwrap(),
+                charged_back: Amount::ZERO,
+            },
+        ],
+        fees: Vec::new(),
+        authorizations: Vec::new(),
+        closed_authorizations: Vec::new(),
+        sequence: 0,
+        accruals: Vec::new(),
+        ledger: Vec::new(),
+        history: Vec::new(),
+    };
+    // Act
+    let actual = super::read_snapshot_from_csv(input)?;
+    // Assert
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn it_reads_a_versioned_snapshot_of_accounts_and_transactions(
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "snapshot,5\n\
+         account,1,5.0,10.0,true,2.5,true,1.25\n\
+         deposit,1,1,20.0,resolved,0,0\n\
+         deposit,1,2,10.0,disputed,2.5,7.5\n\
+        ",
+    );
+    let expected = Snapshot {
+        accounts: vec![Account {
+            client: 1,
+            available: Amount::from_str("5.0").unwrap(),
+            held: Amount::from_str("10.0").unwrap(),
+            status: AccountStatus::Locked,
+            pending_credit: Amount::from_str("2.5").unwrap(),
+            flagged: true,
+            receivable: Amount::from_str("1.25").unwrap(),
+            credit_limit: Amount::ZERO,
+            authorized: Amount::ZERO,
+        }],
+        transactions: vec![
+            Transaction::Deposit {
+                client: 1,
+                tx: 1,
+                amount: Amount::from_str("20.0").unwrap(),
+                state: DisputeState::Resolved,
+                disputed: Amount::ZERO,
+                charged_back: Amount::ZERO,
+            },
+            Transaction::Deposit {
+                client: 1,
+                tx: 2,
+                amount: Amount::from_str("10.0").unwrap(),
+                state: DisputeState::Disputed,
+                disputed: Amount::from_str("2.5").unwrap(),
+                charged_back: Amount::from_str("7.5").unwrap(),
+            },
+        ],
+        fees: Vec::new(),
+        authorizations: Vec::new(),
+        closed_authorizations: Vec::new(),
+        sequence: 0,
+        accruals: Vec::new(),
+        ledger: Vec::new(),
+        history: Vec::new(),
+    };
+    // Act
+    let actual = super::read_snapshot_from_csv(input)?;
+    // Assert
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_read_a_snapshot_of_an_unsupported_version() {
+    // Arrange
+    let input = stringreader::StringReader::new("snapshot,99\naccount,1,5.0,10.0,false\n");
+    // Act
+    let actual = super::read_snapshot_from_csv(input);
+    // Assert
+    assert!(actual.is_err());
+}
+
+#[test]
+fn it_rejects_or_rounds_amounts_with_excess_precision_according_to_the_precision_policy() {
+    // Arrange
+    let input = "type,client,tx,amount\n\
+                 deposit,1,1,1.005\n\
+                 withdrawal,1,2,0.5\n\
+                ";
+    let reject = PrecisionPolicy {
+        max_input_scale: 2,
+        ..PrecisionPolicy::default()
+    };
+    let round = PrecisionPolicy {
+        excess: ExcessPrecision::Round,
+        rounding: RoundingMode::HalfUp,
+        ..reject
+    };
+    // Act
+    let mut rejected = Vec::new();
+    super::process_records_from_csv(input.as_bytes(), &reject, |_, trans| rejected.push(trans));
+    let mut rounded = Vec::new();
+    super::process_records_from_csv(input.as_bytes(), &round, |_, trans| rounded.push(trans));
+    // Assert
+    assert!(matches!(&rejected[0], Err(ParseError::ExcessPrecision(raw)) if raw.line == 2));
+    assert!(rejected[1].is_ok());
+    assert_eq!(
+        Ok(Transaction::Deposit {
+            client: 1,
+            tx: 1,
+            amount: Amount::from_str("1.01").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        }),
+        rounded[0]
+    );
+}
+
+#[test]
+fn it_reports_negative_and_zero_deposit_and_withdrawal_amounts_as_non_positive() {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "type,client,tx,amount\n\
+              deposit,1,1,-500.00\n\
+              deposit,1,2,0\n\
+              withdrawal,1,3,-500.00\n\
+              withdrawal,1,4,0.0000\n\
+              deposit,1,5,0.0001\n\
+             ",
+    );
+    // Act
+    let mut actual = Vec::new();
+    super::processs_transactions_from_csv(input, |trans| actual.push(trans));
+    // Assert
+    for (line, actual) in (2..).zip(&actual[..4]) {
+        assert!(
+            matches!(actual, Err(ParseError::NonPositiveAmount(raw)) if raw.line == line),
+            "{actual:?}"
+        );
+    }
+    assert!(actual[4].is_ok());
+}
+
+#[test]
+fn it_reads_an_optional_amount_on_disputes_resolves_and_chargebacks() {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "type,client,tx,amount\n\
+         dispute,1,1,\n\
+         dispute,1,1,25.50\n\
+         resolve,1,1,10\n\
+         chargeback,1,1\n\
+         chargeback,1,1,-1\n\
+        ",
+    );
+    let part = |amount: &str| Some(Amount::from_str(amount).unwrap());
+    let expected = [
+        Transaction::Dispute {
+            client: 1,
+            referenced_tx: 1,
+            amount: None,
+        },
+        Transaction::Dispute {
+            client: 1,
+            referenced_tx: 1,
+            amount: part("25.50"),
+        },
+        Transaction::Resolve {
+            client: 1,
+            referenced_tx: 1,
+            amount: part("10"),
+        },
+        Transaction::ChargeBack {
+            client: 1,
+            referenced_tx: 1,
+            amount: None,
+        },
+    ];
+    // Act
+    let mut actual = Vec::new();
+    super::processs_transactions_from_csv(input, |trans| actual.push(trans));
+    // Assert
+    for (expected, actual) in expected.iter().zip(&actual) {
+        assert_eq!(Ok(expected), actual.as_ref());
+    }
+    assert!(matches!(
+        &actual[4],
+        Err(ParseError::NonPositiveAmount(raw)) if raw.line == 6
+    ));
+}
+
+#[test]
+fn it_reads_unlock_close_and_reopen_admin_transactions() {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "type,client,tx,amount\n\
+         unlock,1,10,\n\
+         close,1,11,\n\
+         reopen,1,12,\n\
+        ",
+    );
+    let expected = [
+        Transaction::Unlock { client: 1, tx: 10 },
+        Transaction::Close { client: 1, tx: 11 },
+        Transaction::Reopen { client: 1, tx: 12 },
+    ];
+    // Act
+    let mut actual = Vec::new();
+    super::processs_transactions_from_csv(input, |trans| actual.push(trans));
+    // Assert
+    assert_eq!(expected.len(), actual.len());
+    for (expected, actual) in expected.iter().zip(&actual) {
+        assert_eq!(Ok(expected), actual.as_ref());
+    }
+}
+
+#[test]
+fn it_reads_the_account_status_of_a_version_6_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "snapshot,6\n\
+         account,1,0,0,closed,0,false,0\n\
+         account,2,5.0,0,locked,0,false,0\n\
+        ",
+    );
+    // Act
+    let actual = super::read_snapshot_from_csv(input)?;
+    // Assert
+    let statuses = actual
+        .accounts
+        .iter()
+        .map(|acct| acct.status)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![AccountStatus::Closed, AccountStatus::Locked], statuses);
+    Ok(())
+}
+
+#[test]
+fn it_reads_stored_transfers_of_a_version_7_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "snapshot,7\n\
+         transfer,1,3,1.5,disputed,1.5,0,2\n\
+        ",
+    );
+    let expected = vec![Transaction::Transfer {
+        client: 1,
+        to: 2,
+        tx: 3,
+        amount: Amount::from_str("1.5").unwrap(),
+        state: DisputeState::Disputed,
+        disputed: Amount::from_str("1.5").unwrap(),
+        charged_back: Amount::ZERO,
+    }];
+    // Act
+    let actual = super::read_snapshot_from_csv(input)?;
+    // Assert
+    assert_eq!(expected, actual.transactions);
+    Ok(())
+}
+
+#[test]
+fn it_reads_a_transfer_with_its_destination_column() {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "type,client,tx,amount,to\n\
+         transfer,1,10,2.5,2\n\
+         deposit,1,11,1.0,\n\
+         transfer,1,12,2.5,\n\
+        ",
+    );
+    // Act
+    let mut actual = Vec::new();
+    super::processs_transactions_from_csv(input, |trans| actual.push(trans));
+    // Assert
+    assert_eq!(
+        Ok(Transaction::Transfer {
+            client: 1,
+            to: 2,
+            tx: 10,
+            amount: Amount::from_str("2.5").unwrap(),
+            state: DisputeState::Settled,
+            disputed: Amount::ZERO,
+            charged_back: Amount::ZERO,
+        }),
+        actual[0]
+    );
+    assert!(actual[1].is_ok());
+    assert!(matches!(
+        &actual[2],
+        Err(ParseError::MissingDestination(raw)) if raw.line == 4
+    ));
+}
+
+#[test]
+fn it_reads_credit_limits_including_a_zero_limit() {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "type,client,tx,amount\n\
+         limit,1,10,250.5\n\
+         limit,1,11,0\n\
+         limit,1,12,\n\
+        ",
+    );
+    // Act
+    let mut actual = Vec::new();
+    super::processs_transactions_from_csv(input, |trans| actual.push(trans));
+    // Assert
+    assert_eq!(
+        Ok(Transaction::CreditLimit {
+            client: 1,
+            tx: 10,
+            amount: Amount::from_str("250.5").unwrap(),
+        }),
+        actual[0]
+    );
+    assert_eq!(
+        Ok(Transaction::CreditLimit {
+            client: 1,
+            tx: 11,
+            amount: Amount::ZERO,
+        }),
+        actual[1]
+    );
+    assert!(matches!(&actual[2], Err(ParseError::MissingAmount(_))));
+}
+
+#[test]
+fn it_reads_a_fee_schedule_from_a_rules_file() -> Result<(), Box<dyn std::error::Error>> {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "fee,flat,percent\n\
+         withdrawal,0.50,1.5\n\
+         chargeback,15,\n\
+         maintenance,2,\n\
+        ",
+    );
+    let expected = FeeSchedule {
+        withdrawal_flat: Amount::from_str("0.5").unwrap(),
+        withdrawal_percent: Amount::from_str("1.5").unwrap(),
+        chargeback: Amount::from_str("15").unwrap(),
+        maintenance: Amount::from_str("2").unwrap(),
+    };
+    // Act
+    let actual = super::read_fee_schedule_from_csv(input)?;
+    // Assert
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_read_fee_rules_with_unknown_negative_or_percentage_only_fees() {
+    // Arrange
+    let inputs = [
+        "fee,flat,percent\nbogus,1,\n",
+        "fee,flat,percent\nwithdrawal,-1,\n",
+        "fee,flat,percent\nchargeback,1,2\n",
+    ];
+    // Act
+    let actual = inputs
+        .map(|input| super::read_fee_schedule_from_csv(stringreader::StringReader::new(input)));
+    // Assert
+    for actual in actual {
+        assert!(actual.is_err());
+    }
+}
+
+#[test]
+fn it_reads_authorize_capture_and_void_transactions() {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "type,client,tx,amount\n\
+         authorize,1,10,25.0\n\
+         capture,1,10,\n\
+         capture,1,10,20.0\n\
+         void,1,10,\n\
+         authorize,1,11,\n\
+        ",
+    );
+    let expected = [
+        Transaction::Authorize {
+            client: 1,
+            tx: 10,
+            amount: Amount::from_str("25.0").unwrap(),
+        },
+        Transaction::Capture {
+            client: 1,
+            tx: 10,
+            amount: None,
+        },
+        Transaction::Capture {
+            client: 1,
+            tx: 10,
+            amount: Some(Amount::from_str("20.0").unwrap()),
+        },
+        Transaction::Void { client: 1, tx: 10 },
+    ];
+    // Act
+    let mut actual = Vec::new();
+    super::processs_transactions_from_csv(input, |trans| actual.push(trans));
+    // Assert
+    for (expected, actual) in expected.iter().zip(&actual) {
+        assert_eq!(Ok(expected), actual.as_ref());
+    }
+    assert!(matches!(&actual[4], Err(ParseError::MissingAmount(_))));
+}
+
+#[test]
+fn it_reads_open_authorizations_and_the_sequence_of_a_version_10_snapshot(
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "snapshot,10\n\
+         account,1,5.0,0,active,0,false,0,0,20.0\n\
+         authorization,1,4,20.0,3\n\
+         sequence,7\n\
+        ",
+    );
+    let expected = vec![Authorization {
+        client: 1,
+        tx: 4,
+        amount: Amount::from_str("20.0").unwrap(),
+        authorized_at: 3,
+    }];
+    // Act
+    let actual = super::read_snapshot_from_csv(input)?;
+    // Assert
+    assert_eq!(
+        Amount::from_str("20.0").unwrap(),
+        actual.accounts[0].authorized
+    );
+    assert_eq!(expected, actual.authorizations);
+    assert_eq!(7, actual.sequence);
+    Ok(())
+}
+
+#[test]
+fn it_reads_accrue_rows_with_a_date_in_the_amount_column() {
+    // Arrange
+    let input = stringreader::StringReader::new(
+        "type,client,tx,amount\n\
+         accrue,1,10,2024-01-31\n\
+         accrue,1,11,2024-02-30\n\
+         accrue,1,12,\n\
+        ",
+    );
+    // Act
+    let mut actual = Vec::new();
+    super::processs_transactions_from_csv(input, |trans| actual.push(trans));
+    // Assert
+    assert_eq!(
+        Ok(Transaction::Accrue {
+            client: 1,
+            tx: 10,
+            as_of: Date::from_str("2024-01-31").unwrap(),
+        }),
+        actual[0]
+    );
+    assert!(matches!(&actual[1], Err(ParseError::InvalidDate(raw)) if raw.line == 3));
+    assert!(matches!(&actual[2], Err(ParseError::InvalidDate(raw)) if raw.line == 4));
+}
+
+#[test]
+fn it_reads_interest_rate_tiers_and_accruals_of_a_version_11_snapshot(
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Arrange
+    let rules = stringreader::StringReader::new(
+        "kind,from,rate\n\
+         credit,0,1.5\n\
+         credit,10000,2.25\n\
+         debit,0,18\n\
+        ",
+    );
+    let snapshot = stringreader::StringReader::new(
+        "snapshot,11\n\
+         sequence,3\n\
+         accrual,1,2024-01-31\n\
+        ",
+    );
+    let tier = |from, rate| RateTier {
+        from: Amount::from_str(from).unwrap(),
+        rate: Amount::from_str(rate).unwrap(),
+    };
+    // Act
+    let schedule = super::read_interest_schedule_from_csv(rules)?;
+    let actual = super::read_snapshot_from_csv(snapshot)?;
+    // Assert
+    assert_eq!(
+        vec![tier("0", "1.5"), tier("10000", "2.25")],
+        schedule.credit
+    );
+    assert_eq!(vec![tier("0", "18")], schedule.debit);
+    assert_eq!(
+        vec![Accrual {
+            client: 1,
+            as_of: Date::from_str("2024-01-31").unwrap(),
+        }],
+        actual.accruals
+    );
+    Ok(())
+}
+
+#[test]
+fn it_reads_the_bank_ledger_balances_of_a_version_12_snapshot(
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Arrange
+    let snapshot = stringreader::StringReader::new(
+        "snapshot,12\n\
+         account,1,85.0000,0.0000,active,0.0000,false,0.0000,0.0000,0.0000\n\
+         sequence,2\n\
+         ledger,bank-cash,100.0000\n\
+         ledger,fee-income,-15.0000\n\
+        ",
+    );
+    let invalid = stringreader::StringReader::new("snapshot,12\nledger,bank-vault,1.0\n");
+    // Act
+    let actual = super::read_snapshot_from_csv(snapshot)?;
+    let bank = Bank::restore(actual.clone()).unwrap();
+    // Assert
+    assert_eq!(
+        vec![
+            LedgerBalance {
+                account: LedgerAccount::Cash,
+                balance: Amount::from_str("100").unwrap(),
+            },
+            LedgerBalance {
+                account: LedgerAccount::FeeIncome,
+                balance: Amount::from_str("-15").unwrap(),
+            },
+        ],
+        actual.ledger
+    );
+    assert_eq!(
+        Amount::from_str("-85").unwrap(),
+        bank.ledger().balance(LedgerAccount::Available(1))
+    );
+    assert!(bank.ledger().is_balanced());
+    assert!(super::read_snapshot_from_csv(invalid).is_err());
+    Ok(())
+}
+
+#[test]
+fn it_reads_the_account_history_of_a_version_13_snapshot() -> Result<(), Box<dyn std::error::Error>>
+{
+    // Arrange
+    let snapshot = stringreader::StringReader::new(
+        "snapshot,13\n\
+         history,1,10.0000,0.0000,10.0000,credited,1,1,10.0000\n\
+         history,2,4.0000,0.0000,4.0000,transferred,1,2,4.0000,2\n\
+        ",
+    );
+    // Act
+    let actual = super::read_snapshot_from_csv(snapshot)?;
+    // Assert
+    assert_eq!(
+        vec![
+            HistoryEntry {
+                client: 1,
+                effect: AppliedEffect::Credited {
+                    client: 1,
+                    tx: 1,
+                    amount: Amount::from_str("10").unwrap(),
+                },
+                available: Amount::from_str("10").unwrap(),
+                held: Amount::ZERO,
+                total: Amount::from_str("10").unwrap(),
+            },
+            HistoryEntry {
+                client: 2,
+                effect: AppliedEffect::Transferred {
+                    client: 1,
+                    to: 2,
+                    tx: 2,
+                    amount: Amount::from_str("4").unwrap(),
+                },
+                available: Amount::from_str("4").unwrap(),
+                held: Amount::ZERO,
+                total: Amount::from_str("4").unwrap(),
+            },
+        ],
+        actual.history
+    );
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_read_interest_rules_with_unknown_kinds_or_negative_rates() {
+    // Arrange
+    let inputs = [
+        "kind,from,rate\nsavings,0,1\n",
+        "kind,from,rate\ncredit,0,-1\n",
+    ];
+    // Act
+    let actual = inputs.map(|input| {
+        super::read_interest_schedule_from_csv(stringreader::StringReader::new(input))
+    });
+    // Assert
+    for actual in actual {
+        assert!(actual.is_err());
+    }
+}
@@ -2,7 +2,17 @@ use std::{error, io};
 
 use serde::Serialize;
 
-use crate::bank::Account;
+use crate::{
+    amount::{Amount, PrecisionPolicy},
+    bank::{
+        ledger::Ledger, policy::ShortfallPolicy, Account, AccountStatus, AppliedEffect,
+        HistoryEntry, Rejection, Snapshot, Transaction,
+    },
+    io::{
+        read::{ParseError, RawRecord},
+        SNAPSHOT_VERSION,
+    },
+};
 
 #[cfg(test)]
 mod tests;
@@ -10,10 +20,201 @@ mod tests;
 pub fn write_accounts_to_csv<'a>(
     writer: &mut impl io::Write,
     accounts: impl Iterator<Item = &'a Account>,
+    precision: &PrecisionPolicy,
+    shortfall_policy: ShortfallPolicy,
 ) -> Result<(), Box<dyn error::Error>> {
     let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(writer);
     for account in accounts {
-        writer.serialize(AccountRecord::from(account))?;
+        writer.serialize(AccountRecord::with_shortfall_policy(
+            account,
+            precision,
+            shortfall_policy,
+        ))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_rejects_to_csv<'a>(
+    writer: &mut impl io::Write,
+    rejects: impl Iterator<Item = &'a RejectRecord>,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut writer = csv::WriterBuilder::new().from_writer(writer);
+    for reject in rejects {
+        writer.serialize(reject)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_journal_to_csv<'a>(
+    writer: &mut impl io::Write,
+    journal: impl Iterator<Item = &'a AppliedEffect>,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_writer(writer);
+    for effect in journal {
+        writer.serialize(JournalRecord::from(effect))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_statement_to_csv<'a>(
+    writer: &mut impl io::Write,
+    history: impl Iterator<Item = &'a HistoryEntry>,
+    precision: &PrecisionPolicy,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut writer = csv::WriterBuilder::new().from_writer(writer);
+    for entry in history {
+        writer.serialize(StatementRecord::new(entry, precision))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_trial_balance_to_csv(
+    writer: &mut impl io::Write,
+    ledger: &Ledger,
+    precision: &PrecisionPolicy,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut writer = csv::WriterBuilder::new().from_writer(writer);
+    for balance in ledger.balances() {
+        let (debit, credit) = match balance.balance.is_negative() {
+            true => (Amount::ZERO, balance.balance.abs()),
+            false => (balance.balance, Amount::ZERO),
+        };
+        writer.serialize(TrialBalanceRecord {
+            account: balance.account.to_string(),
+            debit: precision.format(debit),
+            credit: precision.format(credit),
+        })?;
+    }
+    let totals = ledger.trial_balance()?;
+    writer.serialize(TrialBalanceRecord {
+        account: "total".to_string(),
+        debit: precision.format(totals.debits),
+        credit: precision.format(totals.credits),
+    })?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_snapshot_to_csv(
+    writer: &mut impl io::Write,
+    snapshot: &Snapshot,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_writer(writer);
+    writer.serialize(("snapshot", SNAPSHOT_VERSION))?;
+    for acct in &snapshot.accounts {
+        writer.serialize((
+            "account",
+            acct.client,
+            acct.available.to_string(),
+            acct.held.to_string(),
+            acct.status.as_str(),
+            acct.pending_credit.to_string(),
+            acct.flagged,
+            acct.receivable.to_string(),
+            acct.credit_limit.to_string(),
+            acct.authorized.to_string(),
+        ))?;
+    }
+    for transaction in &snapshot.transactions {
+        match transaction {
+            Transaction::Deposit {
+                client,
+                tx,
+                amount,
+                state,
+                disputed,
+                charged_back,
+            } => writer.serialize((
+                "deposit",
+                client,
+                tx,
+                amount.to_string(),
+                state.as_str(),
+                disputed.to_string(),
+                charged_back.to_string(),
+            ))?,
+            Transaction::Withdrawal {
+                client,
+                tx,
+                amount,
+                state,
+                disputed,
+                charged_back,
+            } => writer.serialize((
+                "withdrawal",
+                client,
+                tx,
+                amount.to_string(),
+                state.as_str(),
+                disputed.to_string(),
+                charged_back.to_string(),
+            ))?,
+            Transaction::Transfer {
+                client,
+                to,
+                tx,
+                amount,
+                state,
+                disputed,
+                charged_back,
+            } => writer.serialize((
+                "transfer",
+                client,
+                tx,
+                amount.to_string(),
+                state.as_str(),
+                disputed.to_string(),
+                charged_back.to_string(),
+                to,
+            ))?,
+            _ => (),
+        }
+    }
+    for fee in &snapshot.fees {
+        writer.serialize(("fee", fee.client, fee.tx, fee.amount.to_string()))?;
+    }
+    for auth in &snapshot.authorizations {
+        writer.serialize((
+            "authorization",
+            auth.client,
+            auth.tx,
+            auth.amount.to_string(),
+            auth.authorized_at,
+        ))?;
+    }
+    for closed in &snapshot.closed_authorizations {
+        writer.serialize(("closed-authorization", closed.client, closed.tx))?;
+    }
+    writer.serialize(("sequence", snapshot.sequence))?;
+    for accrual in &snapshot.accruals {
+        writer.serialize(("accrual", accrual.client, accrual.as_of.to_string()))?;
+    }
+    for balance in &snapshot.ledger {
+        writer.serialize((
+            "ledger",
+            balance.account.to_string(),
+            balance.balance.to_string(),
+        ))?;
+    }
+    for entry in &snapshot.history {
+        writer.serialize((
+            "history",
+            entry.client,
+            entry.available.to_string(),
+            entry.held.to_string(),
+            entry.total.to_string(),
+            JournalRecord::from(&entry.effect),
+        ))?;
     }
     writer.flush()?;
     Ok(())
@@ -26,28 +227,185 @@ pub struct AccountRecord {
     held: String,
     total: String,
     locked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flagged: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    receivable: Option<String>,
 }
 
-impl From<Account> for AccountRecord {
-    fn from(account: Account) -> Self {
+impl AccountRecord {
+    pub fn new(account: &Account, precision: &PrecisionPolicy) -> Self {
+        Self::with_shortfall_policy(account, precision, ShortfallPolicy::default())
+    }
+
+    pub fn with_shortfall_policy(
+        account: &Account,
+        precision: &PrecisionPolicy,
+        shortfall_policy: ShortfallPolicy,
+    ) -> Self {
+        let (flagged, receivable) = match shortfall_policy {
+            ShortfallPolicy::Flag => (Some(account.flagged), None),
+            ShortfallPolicy::Receivable => (None, Some(precision.format(account.receivable))),
+            ShortfallPolicy::Reject | ShortfallPolicy::AllowNegative => (None, None),
+        };
         Self {
             client: account.client,
-            available: account.available.to_string(),
-            held: account.held.to_string(),
-            total: account.total().to_string(),
-            locked: account.locked,
+            available: precision.format(account.available),
+            held: precision.format(account.held),
+            total: account
+                .total()
+                .map_or_else(|err| err.to_string(), |total| precision.format(total)),
+            locked: account.status == AccountStatus::Locked,
+            flagged,
+            receivable,
         }
     }
 }
 
+impl From<Account> for AccountRecord {
+    fn from(account: Account) -> Self {
+        Self::new(&account, &PrecisionPolicy::default())
+    }
+}
+
 impl From<&Account> for AccountRecord {
     fn from(account: &Account) -> Self {
+        Self::new(account, &PrecisionPolicy::default())
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct StatementRecord {
+    tx: u32,
+    effect: &'static str,
+    amount: String,
+    counterparty: Option<u16>,
+    available: String,
+    held: String,
+    total: String,
+}
+
+impl StatementRecord {
+    pub fn new(entry: &HistoryEntry, precision: &PrecisionPolicy) -> Self {
+        let (effect, amount) = effect_name_and_amount(&entry.effect);
+        let counterparty = match entry.effect {
+            AppliedEffect::Transferred { client, to, .. }
+            | AppliedEffect::Returned { client, to, .. } => match entry.client == client {
+                true => Some(to),
+                false => Some(client),
+            },
+            _ => None,
+        };
         Self {
-            client: account.client,
-            available: account.available.to_string(),
-            held: account.held.to_string(),
-            total: account.total().to_string(),
-            locked: account.locked,
+            tx: entry.effect.tx(),
+            effect,
+            amount: precision.format(*amount),
+            counterparty,
+            available: precision.format(entry.available),
+            held: precision.format(entry.held),
+            total: precision.format(entry.total),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TrialBalanceRecord {
+    account: String,
+    debit: String,
+    credit: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RejectRecord {
+    line: u64,
+    #[serde(rename = "type")]
+    trans_type: String,
+    client: String,
+    tx: String,
+    amount: String,
+    to: String,
+    reason: &'static str,
+    detail: String,
+}
+
+impl RejectRecord {
+    pub fn unparsed(err: &ParseError) -> Self {
+        Self::new(err.raw(), err.code(), err.to_string())
+    }
+
+    pub fn rejected(raw: &RawRecord, rejection: &Rejection) -> Self {
+        Self::new(raw, rejection.code(), rejection.to_string())
+    }
+
+    fn new(raw: &RawRecord, reason: &'static str, detail: String) -> Self {
+        let field = |i: usize| raw.fields.get(i).cloned().unwrap_or_default();
+        Self {
+            line: raw.line,
+            trans_type: field(0),
+            client: field(1),
+            tx: field(2),
+            amount: field(3),
+            to: field(4),
+            reason,
+            detail,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct JournalRecord {
+    effect: &'static str,
+    client: u16,
+    tx: u32,
+    amount: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl From<&AppliedEffect> for JournalRecord {
+    fn from(effect: &AppliedEffect) -> Self {
+        let (name, amount) = effect_name_and_amount(effect);
+        let detail = match *effect {
+            AppliedEffect::Transferred { to, .. } | AppliedEffect::Returned { to, .. } => {
+                Some(to.to_string())
+            }
+            AppliedEffect::InterestPosted { as_of, .. } => Some(as_of.to_string()),
+            _ => None,
+        };
+        Self {
+            effect: name,
+            client: effect.client(),
+            tx: effect.tx(),
+            amount: amount.to_string(),
+            detail,
         }
     }
 }
+
+fn effect_name_and_amount(effect: &AppliedEffect) -> (&'static str, &Amount) {
+    match effect {
+        AppliedEffect::Unlocked { .. } => ("unlocked", &Amount::ZERO),
+        AppliedEffect::Closed { .. } => ("closed", &Amount::ZERO),
+        AppliedEffect::Reopened { .. } => ("reopened", &Amount::ZERO),
+        AppliedEffect::CreditLimitSet { amount, .. } => ("credit_limit_set", amount),
+        AppliedEffect::FeeCharged { amount, .. } => ("fee_charged", amount),
+        AppliedEffect::FeeRefunded { amount, .. } => ("fee_refunded", amount),
+        AppliedEffect::Authorized { amount, .. } => ("authorized", amount),
+        AppliedEffect::Captured { amount, .. } => ("captured", amount),
+        AppliedEffect::Voided { amount, .. } => ("voided", amount),
+        AppliedEffect::Expired { amount, .. } => ("expired", amount),
+        AppliedEffect::InterestPosted { amount, .. } => ("interest_posted", amount),
+        AppliedEffect::Credited { amount, .. } => ("credited", amount),
+        AppliedEffect::Debited { amount, .. } => ("debited", amount),
+        AppliedEffect::Transferred { amount, .. } => ("transferred", amount),
+        AppliedEffect::Held { amount, .. } => ("held", amount),
+        AppliedEffect::HeldFlagged { amount, .. } => ("held_flagged", amount),
+        AppliedEffect::HeldWithReceivable { amount, .. } => ("held_with_receivable", amount),
+        AppliedEffect::Released { amount, .. } => ("released", amount),
+        AppliedEffect::Reversed { amount, .. } => ("reversed", amount),
+        AppliedEffect::Returned { amount, .. } => ("returned", amount),
+        AppliedEffect::PendingRaised { amount, .. } => ("pending_raised", amount),
+        AppliedEffect::PendingDropped { amount, .. } => ("pending_dropped", amount),
+        AppliedEffect::PendingGranted { amount, .. } => ("pending_granted", amount),
+    }
+}
@@ -1,55 +1,491 @@
 use std::{error, str::FromStr};
 
-use bigdecimal::BigDecimal;
+use crate::amount::Amount;
 
-use crate::bank::Account;
+use crate::{
+    amount::PrecisionPolicy,
+    bank::{
+        ledger::{LedgerAccount, LedgerBalance},
+        policy::ShortfallPolicy,
+        Account, AccountStatus, Accrual, AppliedEffect, Authorization, Bank, ClosedAuthorization,
+        DisputeState, Fee, HistoryEntry, Rejection, Snapshot, Transaction,
+    },
+    date::Date,
+    io::read::{ParseError, RawRecord},
+};
+
+use super::RejectRecord;
 
 #[test]
 fn it_writes_all_account_amount_and_locked_combinations() -> Result<(), Box<dyn error::Error>> {
     // Arrange
     let acct1 = Account {
         client: 1,
-        available: BigDecimal::from_str("0.0").unwrap(),
-        held: BigDecimal::from_str("0.0").unwrap(),
-        locked: false,
+        available: Amount::from_str("0.0").unwrap(),
+        held: Amount::from_str("0.0").unwrap(),
+        status: AccountStatus::Active,
+        pending_credit: Amount::ZERO,
+        flagged: false,
+        receivable: Amount::ZERO,
+        credit_limit: Amount::ZERO,
+        authorized: Amount::ZERO,
     };
     let acct2 = Account {
         client: 2,
-        available: BigDecimal::from_str("10.0").unwrap(),
-        held: BigDecimal::from_str("0.0").unwrap(),
-        locked: false,
+        available: Amount::from_str("10.0").unwrap(),
+        held: Amount::from_str("0.0").unwrap(),
+        status: AccountStatus::Active,
+        pending_credit: Amount::ZERO,
+        flagged: false,
+        receivable: Amount::ZERO,
+        credit_limit: Amount::ZERO,
+        authorized: Amount::ZERO,
     };
     let acct3 = Account {
         client: 3,
-        available: BigDecimal::from_str("0.0").unwrap(),
-        held: BigDecimal::from_str("20.0").unwrap(),
-        locked: false,
+        available: Amount::from_str("0.0").unwrap(),
+        held: Amount::from_str("20.0").unwrap(),
+        status: AccountStatus::Active,
+        pending_credit: Amount::ZERO,
+        flagged: false,
+        receivable: Amount::ZERO,
+        credit_limit: Amount::ZERO,
+        authorized: Amount::ZERO,
     };
     let acct4 = Account {
         client: 4,
-        available: BigDecimal::from_str("10.0").unwrap(),
-        held: BigDecimal::from_str("20.0").unwrap(),
-        locked: false,
+        available: Amount::from_str("10.0").unwrap(),
+        held: Amount::from_str("20.0").unwrap(),
+        status: AccountStatus::Active,
+        pending_credit: Amount::ZERO,
+        flagged: false,
+        receivable: Amount::ZERO,
+        credit_limit: Amount::ZERO,
+        authorized: Amount::ZERO,
     };
     let acct5 = Account {
         client: 5,
-        available: BigDecimal::from_str("5.0").unwrap(),
-        held: BigDecimal::from_str("0.0").unwrap(),
-        locked: true,
+        available: Amount::from_str("5.0").unwrap(),
+        held: Amount::from_str("0.0").unwrap(),
+        status: AccountStatus::Locked,
+        pending_credit: Amount::ZERO,
+        flagged: false,
+        receivable: Amount::ZERO,
+        credit_limit: Amount::ZERO,
+        authorized: Amount::ZERO,
+    };
+    let acct6 = Account {
+        client: 6,
+        status: AccountStatus::Closed,
+        ..acct1.clone()
     };
-    let accounts = [&acct1, &acct2, &acct3, &acct4, &acct5].into_iter();
+    let accounts = [&acct1, &acct2, &acct3, &acct4, &acct5, &acct6].into_iter();
     let expected = "client,available,held,total,locked\n\
-                         1,0.0,0.0,0.0,false\n\
-                         2,10.0,0.0,10.0,false\n\
-                         3,0.0,20.0,20.0,false\n\
-                         4,10.0,20.0,30.0,false\n\
-                         5,5.0,0.0,5.0,true\n\
+                         1,0.0000,0.0000,0.0000,false\n\
+                         2,10.0000,0.0000,10.0000,false\n\
+                         3,0.0000,20.0000,20.0000,false\n\
+                         4,10.0000,20.0000,30.0000,false\n\
+                         5,5.0000,0.0000,5.0000,true\n\
+                         6,0.0000,0.0000,0.0000,false\n\
                         ";
     // Act
     let mut output = Vec::<u8>::new();
-    super::write_accounts_to_csv(&mut output, accounts)?;
+    super::write_accounts_to_csv(
+        &mut output,
+        accounts,
+        &PrecisionPolicy::default(),
+        ShortfallPolicy::default(),
+    )?;
+    let actual = String::from_utf8(output)?;
+    // Assert
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn it_writes_rejected_and_unparsed_records_with_their_reason_codes(
+) -> Result<(), Box<dyn error::Error>> {
+    // Arrange
+    let rejected = RawRecord {
+        line: 2,
+        byte: 22,
+        fields: vec!["withdrawal".into(), "1".into(), "2".into(), "5.0".into()],
+    };
+    let unparsed = RawRecord {
+        line: 3,
+        byte: 43,
+        fields: vec!["deposit".into(), "1".into(), "3".into()],
+    };
+    let transfer = RawRecord {
+        line: 4,
+        byte: 58,
+        fields: vec![
+            "transfer".into(),
+            "1".into(),
+            "4".into(),
+            "7.0".into(),
+            "2".into(),
+        ],
+    };
+    let rejects = [
+        RejectRecord::rejected(
+            &rejected,
+            &Rejection::InsufficientFunds { client: 1, tx: 2 },
+        ),
+        RejectRecord::unparsed(&ParseError::MissingAmount(unparsed)),
+        RejectRecord::rejected(
+            &transfer,
+            &Rejection::InsufficientFunds { client: 1, tx: 4 },
+        ),
+    ];
+    let expected = "line,type,client,tx,amount,to,reason,detail\n\
+                    2,withdrawal,1,2,5.0,,insufficient_funds,client 1 has insufficient funds for tx 2\n\
+                    3,deposit,1,3,,,missing_amount,\"line 3 (byte 43): missing amount in record [deposit,1,3]\"\n\
+                    4,transfer,1,4,7.0,2,insufficient_funds,client 1 has insufficient funds for tx 4\n\
+                   ";
+    // Act
+    let mut output = Vec::<u8>::new();
+    super::write_rejects_to_csv(&mut output, rejects.iter())?;
+    let actual = String::from_utf8(output)?;
+    // Assert
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn it_writes_a_journal_of_applied_effects_without_headers() -> Result<(), Box<dyn error::Error>> {
+    // Arrange
+    let journal = [
+        AppliedEffect::Credited {
+            client: 1,
+            tx: 1,
+            amount: Amount::from_str("10.0").unwrap(),
+        },
+        AppliedEffect::Held {
+            client: 1,
+            tx: 1,
+            amount: Amount::from_str("10.0").unwrap(),
+        },
+        AppliedEffect::Reversed {
+            client: 1,
+            tx: 1,
+            amount: Amount::from_str("10.0").unwrap(),
+        },
+        AppliedEffect::Transferred {
+            client: 2,
+            to: 1,
+            tx: 2,
+            amount: Amount::from_str("5.0").unwrap(),
+        },
+        AppliedEffect::InterestPosted {
+            client: 2,
+            tx: 3,
+            amount: Amount::from_str("-0.0125").unwrap(),
+            as_of: Date::from_str("2024-01-31").unwrap(),
+        },
+    ];
+    let expected = "credited,1,1,10.0000\n\
+                    held,1,1,10.0000\n\
+                    reversed,1,1,10.0000\n\
+                    transferred,2,2,5.0000,1\n\
+                    interest_posted,2,3,-0.0125,2024-01-31\n\
+                   ";
+    // Act
+    let mut output = Vec::<u8>::new();
+    super::write_journal_to_csv(&mut output, journal.iter())?;
+    let actual = String::from_utf8(output)?;
+    // Assert
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn it_writes_a_versioned_snapshot_of_accounts_and_transactions() -> Result<(), Box<dyn error::Error>>
+{
+    // Arrange
+    let snapshot = Snapshot {
+        accounts: vec![Account {
+            client: 1,
+            available: Amount::from_str("5.0").unwrap(),
+            held: Amount::from_str("10.0").unwrap(),
+            status: AccountStatus::Active,
+            pending_credit: Amount::from_str("2.5").unwrap(),
+            flagged: true,
+            receivable: Amount::from_str("1.25").unwrap(),
+            credit_limit: Amount::from_str("50").unwrap(),
+            authorized: Amount::from_str("3.0").unwrap(),
+        }],
+        transactions: vec![
+            Transaction::Deposit {
+                client: 1,
+                tx: 1,
+                amount: Amount::from_str("20.0").unwrap(),
+                state: DisputeState::Settled,
+                disputed: Amount::ZERO,
+                charged_back: Amount::ZERO,
+            },
+            Transaction::Withdrawal {
+                client: 1,
+                tx: 2,
+                amount: Amount::from_str("10.0").unwrap(),
+                state: DisputeState::ChargedBack,
+                disputed: Amount::ZERO,
+                charged_back: Amount::from_str("10.0").unwrap(),
+            },
+            Transaction::Transfer {
+                client: 1,
+                to: 2,
+                tx: 3,
+                amount: Amount::from_str("1.5").unwrap(),
+                state: DisputeState::Disputed,
+                disputed: Amount::from_str("1.5").unwrap(),
+                charged_back: Amount::ZERO,
+            },
+        ],
+        fees: vec![Fee {
+            client: 1,
+            tx: 2,
+            amount: Amount::from_str("0.5").unwrap(),
+        }],
+        authorizations: vec![Authorization {
+            client: 1,
+            tx: 4,
+            amount: Amount::from_str("3.0").unwrap(),
+            authorized_at: 3,
+        }],
+        closed_authorizations: vec![ClosedAuthorization { client: 1, tx: 6 }],
+        sequence: 5,
+        accruals: vec![Accrual {
+            client: 1,
+            as_of: Date::from_str("2024-01-31").unwrap(),
+        }],
+        ledger: vec![LedgerBalance {
+            account: LedgerAccount::Cash,
+            balance: Amount::from_str("20.0").unwrap(),
+        }],
+        history: vec![HistoryEntry {
+            client: 2,
+            effect: AppliedEffect::Transferred {
+                client: 1,
+                to: 2,
+                tx: 3,
+                amount: Amount::from_str("1.5").unwrap(),
+            },
+            available: Amount::from_str("1.5").unwrap(),
+            held: Amount::ZERO,
+            total: Amount::from_str("1.5").unwrap(),
+        }],
+    };
+    let expected = "snapshot,14\n\
+                    account,1,5.0000,10.0000,active,2.5000,true,1.2500,50.0000,3.0000\n\
+                    deposit,1,1,20.0000,settled,0.0000,0.0000\n\
+                    withdrawal,1,2,10.0000,charged-back,0.0000,10.0000\n\
+                    transfer,1,3,1.5000,disputed,1.5000,0.0000,2\n\
+                    fee,1,2,0.5000\n\
+                    authorization,1,4,3.0000,3\n\
+                    closed-authorization,1,6\n\
+                    sequence,5\n\
+                    accrual,1,2024-01-31\n\
+                    ledger,bank-cash,20.0000\n\
+                    history,2,1.5000,0.0000,1.5000,transferred,1,3,1.5000,2\n\
+                   ";
+    // Act
+    let mut output = Vec::<u8>::new();
+    super::write_snapshot_to_csv(&mut output, &snapshot)?;
+    let actual = String::from_utf8(output)?;
+    // Assert
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn it_writes_a_statement_of_an_account_with_the_counterparty_of_transfers(
+) -> Result<(), Box<dyn error::Error>> {
+    // Arrange
+    let mut bank = Bank::default();
+    bank.apply(Transaction::Deposit {
+        client: 1,
+        tx: 1,
+        amount: Amount::from_str("10.0").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })?;
+    bank.apply(Transaction::Deposit {
+        client: 2,
+        tx: 2,
+        amount: Amount::from_str("1.0").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })?;
+    bank.apply(Transaction::Transfer {
+        client: 1,
+        to: 2,
+        tx: 3,
+        amount: Amount::from_str("4.0").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })?;
+    let expected = "tx,effect,amount,counterparty,available,held,total\n\
+                    2,credited,1.00,,1.00,0.00,1.00\n\
+                    3,transferred,4.00,1,5.00,0.00,5.00\n\
+                   ";
+    let precision = PrecisionPolicy {
+        output_scale: 2,
+        ..PrecisionPolicy::default()
+    };
+    let mut actual = Vec::new();
+    // Act
+    super::write_statement_to_csv(&mut actual, bank.history(2).iter(), &precision)?;
+    // Assert
+    assert_eq!(expected, String::from_utf8(actual)?);
+    Ok(())
+}
+
+#[test]
+fn it_writes_a_trial_balance_of_the_ledger_with_totals() -> Result<(), Box<dyn error::Error>> {
+    // Arrange
+    let mut bank = Bank::default();
+    bank.apply(Transaction::Deposit {
+        client: 1,
+        tx: 1,
+        amount: Amount::from_str("10.0").unwrap(),
+        state: DisputeState::Settled,
+        disputed: Amount::ZERO,
+        charged_back: Amount::ZERO,
+    })?;
+    bank.apply(Transaction::Dispute {
+        client: 1,
+        referenced_tx: 1,
+        amount: Some(Amount::from_str("4.0").unwrap()),
+    })?;
+    let expected = "account,debit,credit\n\
+                    client-available:1,0.0000,6.0000\n\
+                    client-held:1,0.0000,4.0000\n\
+                    bank-cash,10.0000,0.0000\n\
+                    total,10.0000,10.0000\n\
+                   ";
+    let mut actual = Vec::new();
+    // Act
+    super::write_trial_balance_to_csv(&mut actual, bank.ledger(), &PrecisionPolicy::default())?;
+    // Assert
+    assert_eq!(expected, String::from_utf8(actual)?);
+    Ok(())
+}
+
+#[test]
+fn it_writes_account_amounts_with_the_output_scale_of_the_precision_policy(
+) -> Result<(), Box<dyn error::Error>> {
+    // Arrange
+    let acct = Account {
+        client: 1,
+        available: Amount::from_str("6899.8").unwrap(),
+        held: Amount::from_str("0.005").unwrap(),
+        status: AccountStatus::Active,
+        pending_credit: Amount::ZERO,
+        flagged: false,
+        receivable: Amount::ZERO,
+        credit_limit: Amount::ZERO,
+        authorized: Amount::ZERO,
+    };
+    let precision = PrecisionPolicy {
+        output_scale: 2,
+        ..PrecisionPolicy::default()
+    };
+    let expected = "client,available,held,total,locked\n\
+                    1,6899.80,0.00,6899.80,false\n\
+                   ";
+    // Act
+    let mut output = Vec::<u8>::new();
+    super::write_accounts_to_csv(
+        &mut output,
+        [&acct].into_iter(),
+        &precision,
+        ShortfallPolicy::default(),
+    )?;
+    let actual = String::from_utf8(output)?;
+    // Assert
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+fn it_writes_overdrawn_accounts_with_negative_balances() -> Result<(), Box<dyn error::Error>> {
+    // Arrange
+    let acct = Account {
+        client: 1,
+        available: Amount::from_str("-0.72").unwrap(),
+        held: Amount::from_str("0.5").unwrap(),
+        status: AccountStatus::Active,
+        pending_credit: Amount::ZERO,
+        flagged: false,
+        receivable: Amount::ZERO,
+        credit_limit: Amount::from_str("100").unwrap(),
+        authorized: Amount::ZERO,
+    };
+    let expected = "client,available,held,total,locked\n\
+                    1,-0.7200,0.5000,-0.2200,false\n\
+                   ";
+    // Act
+    let mut output = Vec::<u8>::new();
+    super::write_accounts_to_csv(
+        &mut output,
+        [&acct].into_iter(),
+        &PrecisionPolicy::default(),
+        ShortfallPolicy::default(),
+    )?;
     let actual = String::from_utf8(output)?;
     // Assert
     assert_eq!(expected, actual);
     Ok(())
 }
+
+#[test]
+fn it_writes_the_flag_or_receivable_column_of_the_shortfall_policy(
+) -> Result<(), Box<dyn error::Error>> {
+    // Arrange
+    let acct = Account {
+        client: 1,
+        available: Amount::ZERO,
+        held: Amount::from_str("100").unwrap(),
+        status: AccountStatus::Active,
+        pending_credit: Amount::ZERO,
+        flagged: true,
+        receivable: Amount::from_str("70").unwrap(),
+        credit_limit: Amount::ZERO,
+        authorized: Amount::ZERO,
+    };
+    let expected = [
+        (
+            ShortfallPolicy::Flag,
+            "client,available,held,total,locked,flagged\n\
+             1,0.0000,100.0000,100.0000,false,true\n",
+        ),
+        (
+            ShortfallPolicy::Receivable,
+            "client,available,held,total,locked,receivable\n\
+             1,0.0000,100.0000,100.0000,false,70.0000\n",
+        ),
+        (
+            ShortfallPolicy::Reject,
+            "client,available,held,total,locked\n\
+             1,0.0000,100.0000,100.0000,false\n",
+        ),
+    ];
+    for (shortfall_policy, expected) in expected {
+        // Act
+        let mut output = Vec::<u8>::new();
+        super::write_accounts_to_csv(
+            &mut output,
+            [&acct].into_iter(),
+            &PrecisionPolicy::default(),
+            shortfall_policy,
+        )?;
+        let actual = String::from_utf8(output)?;
+        // Assert
+        assert_eq!(expected, actual);
+    }
+    Ok(())
+}
@@ -1,2 +1,5 @@
+pub mod amount;
 pub mod bank;
+pub mod date;
+pub mod engine;
 pub mod io;
@@ -1,12 +1,25 @@
 use std::{
     error,
-    fs::File,
+    fs::{File, OpenOptions},
     io::{stdout, BufReader},
+    thread,
 };
 
 use simple_xact::{
-    bank::Bank,
-    io::{read::processs_transactions_from_csv, write::write_accounts_to_csv},
+    amount::PrecisionPolicy,
+    bank::{policy::InterestSchedule, Bank},
+    engine::{Engine, Outcome, Recorded},
+    io::{
+        read::{
+            process_records_from_csv, read_fee_schedule_from_csv, read_interest_schedule_from_csv,
+            read_journal_from_csv, read_snapshot_from_csv,
+        },
+        write::{
+            write_accounts_to_csv, write_journal_to_csv, write_rejects_to_csv,
+            write_snapshot_to_csv, write_statement_to_csv, write_trial_balance_to_csv,
+            RejectRecord,
+        },
+    },
 };
 
 use clap::StructOpt;
@@ -14,13 +27,99 @@ use clap::StructOpt;
 mod cli;
 
 pub fn main() -> Result<(), Box<dyn error::Error>> {
-    let reader = BufReader::new(File::open(cli::Arguments::parse().path)?);
-    let mut bank = Bank::default();
-    processs_transactions_from_csv(reader, |transaction| {
-        if let Ok(transaction) = transaction {
-            bank.apply(transaction);
+    let args = cli::Arguments::parse();
+    let reader = BufReader::new(File::open(args.path)?);
+    let precision = PrecisionPolicy {
+        max_input_scale: args.max_scale,
+        excess: args.excess_precision,
+        rounding: args.rounding,
+        output_scale: args.output_scale,
+    };
+    let mut bank = match (args.replay, args.load_snapshot) {
+        (Some(path), _) => Bank::replay(read_journal_from_csv(BufReader::new(File::open(path)?))?)
+            .map_err(|err| format!("Cannot replay journal: {err}"))?,
+        (_, Some(path)) => {
+            Bank::restore(read_snapshot_from_csv(BufReader::new(File::open(path)?))?)
+                .map_err(|err| format!("Cannot restore snapshot: {err}"))?
         }
+        (None, None) => Bank::default(),
+    }
+    .with_freeze_policy(args.freeze_policy)
+    .with_dispute_policy(args.dispute_policy)
+    .with_shortfall_policy(args.shortfall_policy)
+    .with_precision_policy(precision)
+    .with_client_mismatch_flagging(args.flag_client_mismatch)
+    .with_authorization_expiry(args.authorization_expiry);
+    if let Some(path) = args.fee_rules {
+        bank = bank.with_fee_schedule(read_fee_schedule_from_csv(BufReader::new(File::open(
+            path,
+        )?))?);
+    }
+    let interest_schedule = match args.interest_rules {
+        Some(path) => read_interest_schedule_from_csv(BufReader::new(File::open(path)?))?,
+        None => InterestSchedule::default(),
+    };
+    bank = bank.with_interest_schedule(InterestSchedule {
+        day_count: args.day_count,
+        ..interest_schedule
     });
-    write_accounts_to_csv(&mut stdout(), bank.balances())?;
+    let mut journal = Vec::new();
+    let mut rejects = Vec::new();
+    let recorded = Recorded {
+        applied: args.journal.is_some(),
+        rejected: args.rejects.is_some(),
+    };
+    // Shards on more threads than cores only add the cost of routing rows.
+    let threads = args
+        .threads
+        .min(thread::available_parallelism().map_or(1, usize::from));
+    let mut engine = Engine::new(bank, threads, recorded, |outcome| match outcome {
+        Outcome::Applied(effects) => journal.extend(effects),
+        Outcome::Rejected(raw, rejection) => rejects.push(RejectRecord::rejected(&raw, &rejection)),
+        Outcome::Unparsed(err) => rejects.push(RejectRecord::unparsed(&err)),
+    });
+    process_records_from_csv(reader, &precision, |raw, transaction| {
+        engine.process(raw, transaction)
+    });
+    let mut bank = engine.finish()?;
+    if let Some(as_of) = args.accrue_as_of {
+        journal.extend(bank.accrue(as_of));
+    }
+    match args.command {
+        Some(cli::Command::Statement { client }) => {
+            write_statement_to_csv(&mut stdout(), bank.history(client).iter(), &precision)?
+        }
+        None => write_accounts_to_csv(
+            &mut stdout(),
+            bank.balances(),
+            &precision,
+            args.shortfall_policy,
+        )?,
+    }
+    if let Some(path) = args.rejects {
+        write_rejects_to_csv(&mut File::create(path)?, rejects.iter())?;
+    }
+    if let Some(path) = args.journal {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        write_journal_to_csv(&mut file, journal.iter())?;
+    }
+    if let Some(path) = args.save_snapshot {
+        write_snapshot_to_csv(&mut File::create(path)?, &bank.snapshot())?;
+    }
+    if let Some(path) = args.trial_balance {
+        write_trial_balance_to_csv(&mut File::create(path)?, bank.ledger(), &precision)?;
+    }
+    if !bank.ledger().is_balanced() {
+        return Err("Ledger trial balance does not balance".into());
+    }
+    if args.verify {
+        let violations = bank.verify();
+        for violation in &violations {
+            eprintln!("{violation}");
+        }
+        if !violations.is_empty() {
+            return Err(format!("{} bank invariants broken", violations.len()).into());
+        }
+    }
     Ok(())
 }
@@ -1,4 +1,4 @@
-use std::process::Command;
+use std::{env, fs, process::Command};
 
 use assert_cmd::prelude::*;
 use predicates::prelude::predicate::str::contains;
@@ -12,10 +12,10 @@ fn it_works_with_the_test_input_file() -> Result<(), Box<dyn std::error::Error>>
         .success()
         .stdout(
             contains("client,available,held,total,locked")
-                .and(contains("1,6899.80,0,6899.80,false"))
-                .and(contains("2,11000.00,0.00,11000.00,false"))
-                .and(contains("3,11899.60,3000.00,14899.60,false"))
-                .and(contains("4,7899.30,0.00,7899.30,true")),
+                .and(contains("1,6899.8000,0.0000,6899.8000,false"))
+                .and(contains("2,11000.0000,0.0000,11000.0000,false"))
+                .and(contains("3,11899.6000,3000.0000,14899.6000,false"))
+                .and(contains("4,7899.3000,0.0000,7899.3000,true")),
         );
     Ok(())
 }
@@ -30,3 +30,284 @@ fn it_errors_with_file_not_found_if_file_does_not_exist() -> Result<(), Box<dyn
         .stderr(contains("No such file or directory"));
     Ok(())
 }
+
+#[test]
+fn it_writes_a_report_of_rejected_rows_when_asked_to() -> Result<(), Box<dyn std::error::Error>> {
+    let rejects = env::temp_dir().join(format!("simple-xact-rejects-{}.csv", std::process::id()));
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input-with-rejects")
+        .arg("--rejects")
+        .arg(&rejects)
+        .assert()
+        .success()
+        .stdout(contains("1,1000.0000,0.0000,1000.0000,false"));
+    let report = fs::read_to_string(&rejects)?;
+    fs::remove_file(&rejects)?;
+    assert!(report.starts_with("line,type,client,tx,amount,to,reason,detail\n"));
+    assert!(report.contains("3,deposit,2,200,,,missing_amount,"));
+    assert!(report.contains("4,withdrawal,1,300,5000.00,,insufficient_funds,"));
+    assert!(report.contains("5,deposit,1,100,1000.00,,duplicate_transaction,"));
+    assert!(report.contains("6,dispute,3,100,,,unknown_client,"));
+    assert!(report.contains("7,bogus,1,400,1.00,,unknown_type,"));
+    Ok(())
+}
+
+#[test]
+fn it_restores_the_bank_by_replaying_a_journal_written_by_a_previous_run(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let journal = env::temp_dir().join(format!("simple-xact-journal-{}.csv", std::process::id()));
+    let _ = fs::remove_file(&journal);
+    let first = Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input")
+        .arg("--journal")
+        .arg(&journal)
+        .output()?;
+    let replayed = Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input-empty")
+        .arg("--replay")
+        .arg(&journal)
+        .output()?;
+    fs::remove_file(&journal)?;
+    assert!(first.status.success() && replayed.status.success());
+    let sorted_lines = |output: Vec<u8>| -> Result<Vec<String>, std::string::FromUtf8Error> {
+        let mut lines = String::from_utf8(output)?
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        lines.sort();
+        Ok(lines)
+    };
+    assert_eq!(sorted_lines(first.stdout)?, sorted_lines(replayed.stdout)?);
+    Ok(())
+}
+
+#[test]
+fn it_processes_a_new_input_on_top_of_a_saved_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+    let snapshot = env::temp_dir().join(format!("simple-xact-snapshot-{}.csv", std::process::id()));
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input")
+        .arg("--save-snapshot")
+        .arg(&snapshot)
+        .assert()
+        .success();
+    let next = Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input-next")
+        .arg("--load-snapshot")
+        .arg(&snapshot)
+        .assert();
+    fs::remove_file(&snapshot)?;
+    next.success().stdout(
+        contains("1,5899.8000,1000.0000,6899.8000,false")
+            .and(contains("2,11000.0000,0.0000,11000.0000,false"))
+            .and(contains("3,14899.6000,0.0000,14899.6000,false"))
+            .and(contains("4,7899.3000,0.0000,7899.3000,true")),
+    );
+    Ok(())
+}
+
+#[test]
+fn it_writes_amounts_with_the_requested_output_scale() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input")
+        .arg("--output-scale")
+        .arg("2")
+        .assert()
+        .success()
+        .stdout(
+            contains("1,6899.80,0.00,6899.80,false")
+                .and(contains("3,11899.60,3000.00,14899.60,false")),
+        );
+    Ok(())
+}
+
+#[test]
+fn it_shows_the_outcome_of_the_shortfall_policy_in_the_output(
+) -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input-shortfall")
+        .arg("--shortfall-policy")
+        .arg("flag")
+        .assert()
+        .success()
+        .stdout(
+            contains("client,available,held,total,locked,flagged")
+                .and(contains("1,-70.0000,100.0000,30.0000,false,true")),
+        );
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input-shortfall")
+        .arg("--shortfall-policy")
+        .arg("receivable")
+        .assert()
+        .success()
+        .stdout(
+            contains("client,available,held,total,locked,receivable")
+                .and(contains("1,0.0000,100.0000,100.0000,false,70.0000")),
+        );
+    Ok(())
+}
+
+#[test]
+fn it_charges_the_fees_of_a_rules_file_as_separate_journal_entries(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let journal = env::temp_dir().join(format!("simple-xact-fees-{}.csv", std::process::id()));
+    let _ = fs::remove_file(&journal);
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input-fees")
+        .arg("--fee-rules")
+        .arg("test-data/test-cli/fee-rules")
+        .arg("--journal")
+        .arg(&journal)
+        .assert()
+        .success()
+        .stdout(
+            contains("1,87.4000,0.0000,87.4000,false")
+                .and(contains("2,-15.0000,0.0000,-15.0000,true")),
+        );
+    let entries = fs::read_to_string(&journal)?;
+    fs::remove_file(&journal)?;
+    assert!(entries.contains("debited,1,2,10.0000\nfee_charged,1,2,0.6000\n"));
+    assert!(entries.contains("fee_charged,1,3,2.0000\n"));
+    assert!(entries.contains("reversed,2,4,50.0000\nfee_charged,2,4,15.0000\n"));
+    Ok(())
+}
+
+#[test]
+fn it_accrues_interest_as_of_a_date_after_processing_the_input(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let journal = env::temp_dir().join(format!("simple-xact-interest-{}.csv", std::process::id()));
+    let _ = fs::remove_file(&journal);
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input-interest")
+        .arg("--interest-rules")
+        .arg("test-data/test-cli/interest-rules")
+        .arg("--day-count")
+        .arg("actual-360")
+        .arg("--accrue-as-of")
+        .arg("2024-01-31")
+        .arg("--journal")
+        .arg(&journal)
+        .assert()
+        .success()
+        .stdout(contains("1,1003.0000,0.0000,1003.0000,false"));
+    let entries = fs::read_to_string(&journal)?;
+    fs::remove_file(&journal)?;
+    assert!(entries.contains("interest_posted,1,2,0.0000,2024-01-01\n"));
+    assert!(entries.contains("interest_posted,1,0,3.0000,2024-01-31\n"));
+    Ok(())
+}
+
+#[test]
+fn it_writes_a_balanced_trial_balance_of_the_ledger() -> Result<(), Box<dyn std::error::Error>> {
+    let trial_balance = env::temp_dir().join(format!(
+        "simple-xact-trial-balance-{}.csv",
+        std::process::id()
+    ));
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input-fees")
+        .arg("--fee-rules")
+        .arg("test-data/test-cli/fee-rules")
+        .arg("--trial-balance")
+        .arg(&trial_balance)
+        .assert()
+        .success();
+    let rows = fs::read_to_string(&trial_balance)?;
+    fs::remove_file(&trial_balance)?;
+    assert!(rows.starts_with("account,debit,credit\n"));
+    assert!(rows.contains("bank-cash,90.0000,0.0000\n"));
+    assert!(rows.contains("fee-income,0.0000,17.6000\n"));
+    assert!(rows.ends_with("total,105.0000,105.0000\n"));
+    Ok(())
+}
+
+#[test]
+fn it_prints_the_statement_of_a_client_instead_of_the_accounts(
+) -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input")
+        .arg("statement")
+        .arg("2")
+        .assert()
+        .success()
+        .stdout(
+            "tx,effect,amount,counterparty,available,held,total\n\
+             200,credited,2000.0000,,2000.0000,0.0000,2000.0000\n\
+             900,credited,9000.0000,,11000.0000,0.0000,11000.0000\n\
+             200,held,2000.0000,,9000.0000,2000.0000,11000.0000\n\
+             200,released,2000.0000,,11000.0000,0.0000,11000.0000\n",
+        );
+    Ok(())
+}
+
+#[test]
+fn it_fails_the_run_with_a_report_of_broken_invariants_when_verifying(
+) -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input")
+        .arg("--verify")
+        .assert()
+        .success();
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input-empty")
+        .arg("--load-snapshot")
+        .arg("test-data/test-cli/snapshot-corrupted")
+        .arg("--verify")
+        .assert()
+        .failure()
+        .stderr(
+            contains("client 1 holds 5.0000 but has 0.0000 disputed")
+                .and(contains("client 1 is locked without a chargeback"))
+                .and(contains("2 bank invariants broken")),
+        );
+    Ok(())
+}
+
+#[test]
+fn it_fails_the_run_when_a_snapshot_or_journal_overflows_the_ledger(
+) -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input-empty")
+        .arg("--load-snapshot")
+        .arg("test-data/test-cli/snapshot-overflowing")
+        .assert()
+        .failure()
+        .stderr(contains("Cannot restore snapshot: amount overflow"));
+    Command::cargo_bin("simple-xact")?
+        .arg("test-data/test-cli/input-empty")
+        .arg("--replay")
+        .arg("test-data/test-cli/journal-overflowing")
+        .assert()
+        .failure()
+        .stderr(contains("Cannot replay journal: amount overflow"));
+    Ok(())
+}
+
+#[test]
+fn it_produces_the_same_output_on_several_threads_as_on_one(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let run = |threads: &str| -> Result<(Vec<String>, String), Box<dyn std::error::Error>> {
+        let journal = env::temp_dir().join(format!(
+            "simple-xact-threads-{threads}-{}.csv",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&journal);
+        let output = Command::cargo_bin("simple-xact")?
+            .arg("test-data/test-cli/input")
+            .arg("--threads")
+            .arg(threads)
+            .arg("--journal")
+            .arg(&journal)
+            .output()?;
+        assert!(output.status.success());
+        let mut accounts = String::from_utf8(output.stdout)?
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        accounts.sort();
+        let entries = fs::read_to_string(&journal)?;
+        fs::remove_file(&journal)?;
+        Ok((accounts, entries))
+    };
+    let (accounts, entries) = run("1")?;
+    assert_eq!((accounts, entries), run("4")?);
+    Ok(())
+}